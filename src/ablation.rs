@@ -0,0 +1,200 @@
+use *;
+use english::statement;
+
+/// The kind of a constraint in an action.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Constraint {
+    /// An entry in `require`.
+    Require,
+    /// An entry in `prevent`.
+    Prevent,
+    /// An entry in `distinct`.
+    Distinct,
+    /// An entry in `remove`.
+    Remove,
+}
+
+/// A constraint of a stock action, removed to measure whether it matters.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Ablation {
+    /// The name of the stock constructor, e.g. "opens".
+    pub constructor: &'static str,
+    /// The verb of the action.
+    pub verb: Verb,
+    /// The kind of constraint.
+    pub constraint: Constraint,
+    /// The position of the entry in its field.
+    pub index: usize,
+    /// Describes the constraint, e.g. "Open: prevent that object is locked".
+    pub description: String,
+    /// The property added by the action that removes the same property anyway,
+    /// because the adjectives exclude each other, e.g. "object is open".
+    pub masked_by: Option<String>,
+}
+
+/// The expectations in scenarios that break without a constraint.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AblationReport {
+    /// The removed constraint.
+    pub ablation: Ablation,
+    /// The names of the scenarios with failures that did not happen with the constraint.
+    pub broken: Vec<(String, Failure)>,
+}
+
+/// Rebuilds an action with a stock constructor from the participants of an action.
+type Rebuild = fn(&Action) -> Option<Action>;
+
+fn subject(action: &Action) -> Object {
+    let Action::Do {ref subject, ..} = *action;
+    subject.clone()
+}
+
+fn object(action: &Action) -> Object {
+    let Action::Do {ref object, ..} = *action;
+    object.clone()
+}
+
+/// Returns the third participant of an action, e.g. the item given.
+fn third(action: &Action) -> Option<Object> {
+    let Action::Do {ref distinct, ..} = *action;
+    distinct.get(2).cloned()
+}
+
+/// Returns the place where an action puts the first participant it decorates.
+fn place(action: &Action) -> Option<Placement> {
+    let Action::Do {ref decorate, ..} = *action;
+    match decorate.first() {
+        Some(&(_, Object::Placement(ref place))) => Some((**place).clone()),
+        _ => None,
+    }
+}
+
+/// Returns the stock constructors by name, with their actions for variables as participants
+/// and a way to rebuild their actions from the participants of any action.
+fn stock() -> Vec<(&'static str, Action, Rebuild)> {
+    let (s, o, x) = (var("subject"), var("object"), var("other"));
+    vec![
+        ("moves", s.clone().moves(o.clone(), on(x.clone())),
+         |a| Some(subject(a).moves(object(a), place(a)?))),
+        ("gives_item", s.clone().gives_item(o.clone(), x.clone()),
+         |a| Some(subject(a).gives_item(object(a), third(a)?))),
+        ("kills", s.clone().kills(o.clone()), |a| Some(subject(a).kills(object(a)))),
+        ("talk_to", s.clone().talk_to(o.clone()), |a| Some(subject(a).talk_to(object(a)))),
+        ("opens", s.clone().opens(o.clone()), |a| Some(subject(a).opens(object(a)))),
+        ("closes", s.clone().closes(o.clone()), |a| Some(subject(a).closes(object(a)))),
+        ("walks_through", s.clone().walks_through(o.clone()),
+         |a| Some(subject(a).walks_through(object(a)))),
+        ("locks", s.clone().locks(o.clone()), |a| Some(subject(a).locks(object(a)))),
+        ("unlocks", s.clone().unlocks(o.clone()), |a| Some(subject(a).unlocks(object(a)))),
+        ("picks_up", s.clone().picks_up(o.clone()), |a| Some(subject(a).picks_up(object(a)))),
+        ("climbs_to", s.clone().climbs_to(o.clone(), on(x.clone())),
+         |a| Some(subject(a).climbs_to(object(a), place(a)?))),
+        ("carries", s.clone().carries(o.clone()), |a| Some(subject(a).carries(object(a)))),
+        ("puts_down", s.clone().puts_down(o.clone()), |a| Some(subject(a).puts_down(object(a)))),
+        ("stands_on", s.clone().stands_on(o.clone()), |a| Some(subject(a).stands_on(object(a)))),
+        ("leans_toward", s.clone().leans_toward(o.clone()),
+         |a| Some(subject(a).leans_toward(object(a)))),
+        ("sleeps_in", s.clone().sleeps_in(o.clone()), |a| Some(subject(a).sleeps_in(object(a)))),
+        ("wakes_up_in", s.clone().wakes_up_in(o.clone()),
+         |a| Some(subject(a).wakes_up_in(object(a)))),
+        ("plays_against", s.clone().plays_against(o.clone(), x),
+         |a| Some(subject(a).plays_against(object(a), third(a)?))),
+        ("goes_to", s.goes_to(o), |a| Some(subject(a).goes_to(object(a)))),
+    ]
+}
+
+impl Ablation {
+    /// Returns every constraint of the stock actions.
+    pub fn all() -> Vec<Ablation> {
+        let adjectives = Adjectives::standard();
+        let mut res = vec![];
+        for (constructor, action, _) in stock() {
+            let Action::Do {
+                verb, ref decorate, ref require, ref prevent, ref distinct, ref remove, ..
+            } = action;
+            let name = verb.name();
+            let mut push = |constraint: Constraint, index: usize, description: String,
+                            masked_by: Option<String>| {
+                let description = format!("{}: {}", name, description);
+                let verb = verb.clone();
+                res.push(Ablation {constructor, verb, constraint, index, description, masked_by});
+            };
+            for (i, (obj, prop)) in require.iter().enumerate() {
+                push(Constraint::Require, i, format!("require that {}", statement(obj, prop)), None);
+            }
+            for (i, (obj, prop)) in prevent.iter().enumerate() {
+                push(Constraint::Prevent, i, format!("prevent that {}", statement(obj, prop)), None);
+            }
+            for (i, obj) in distinct.iter().enumerate() {
+                let obj = match *obj {
+                    Object::Placement(ref place) => format!("{}", place),
+                    _ => format!("{}", obj),
+                };
+                push(Constraint::Distinct, i, format!("distinct {}", obj), None);
+            }
+            for (i, (obj, prop)) in remove.iter().enumerate() {
+                // Adding an adjective removes the adjectives it excludes.
+                let masked_by = decorate.iter()
+                    .find(|&(d, p)| d == obj && match (p, prop) {
+                        (Object::Adj(a), Object::Adj(b)) => adjectives.excluded(a).contains(b),
+                        _ => false,
+                    })
+                    .map(|(d, p)| statement(d, p));
+                push(Constraint::Remove, i, format!("remove that {}", statement(obj, prop)), masked_by);
+            }
+        }
+        res
+    }
+
+    /// Removes the constraint from an action made by the same stock constructor.
+    ///
+    /// Other actions with the same verb, e.g. from schemas, are left as they are.
+    pub fn apply(&self, action: &Action) -> Action {
+        let stock = stock().into_iter()
+            .find(|&(name, _, _)| name == self.constructor)
+            .and_then(|(_, _, rebuild)| rebuild(action));
+        if stock.as_ref() != Some(action) {return action.clone()};
+        let mut action = action.clone();
+        let Action::Do {ref mut require, ref mut prevent, ref mut distinct, ref mut remove, ..} = action;
+        match self.constraint {
+            Constraint::Require if self.index < require.len() => {require.remove(self.index);}
+            Constraint::Prevent if self.index < prevent.len() => {prevent.remove(self.index);}
+            Constraint::Distinct if self.index < distinct.len() => {distinct.remove(self.index);}
+            Constraint::Remove if self.index < remove.len() => {remove.remove(self.index);}
+            _ => {}
+        }
+        action
+    }
+}
+
+/// Runs named scenarios without each constraint of the stock actions.
+///
+/// Reports the failures that do not happen when running the scenarios as they are.
+pub fn ablate(scenarios: &[(String, Scenario)]) -> Vec<AblationReport> {
+    let baseline: Vec<Vec<Failure>> = scenarios.iter().map(|(_, s)| s.run()).collect();
+    Ablation::all().into_iter().map(|ablation| {
+        let mut broken = vec![];
+        for ((name, scenario), baseline) in scenarios.iter().zip(baseline.iter()) {
+            for failure in scenario.run_with(&|action| ablation.apply(action)) {
+                if !baseline.contains(&failure) {broken.push((name.clone(), failure))};
+            }
+        }
+        AblationReport {ablation, broken}
+    }).collect()
+}
+
+/// Writes reports as a Markdown table, with one row per constraint.
+///
+/// Constraints that the exclusive adjectives make redundant are marked as masked.
+pub fn ablation_table(reports: &[AblationReport]) -> String {
+    let mut res = String::from("| constraint | broken | expectations | masked by |\n|---|---|---|---|\n");
+    for report in reports {
+        let broken: Vec<String> = report.broken.iter()
+            .map(|(name, failure)| format!("{} {}", name, failure))
+            .collect();
+        res.push_str(&format!("| {} | {} | {} | {} |\n", report.ablation.description,
+                              report.broken.len(), broken.join("<br>"),
+                              report.ablation.masked_by.as_deref().unwrap_or("")));
+    }
+    res
+}
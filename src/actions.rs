@@ -52,12 +52,28 @@ impl Object {
         self.gives_item(to, item)
     }
 
+    /// Goes to a place.
+    pub fn goes_to(self, place: Object) -> Action {
+        Action::Do {
+            subject: self.clone(), verb: Verb::GoTo, object: place.clone(),
+            decorate: vec![
+                (self.clone(), in_(place.clone()).into()),
+            ],
+            remove: vec![],
+            remove_placement: vec![
+                self.clone(),
+            ],
+            require: vec![],
+            prevent: vec![],
+            distinct: vec![self, place],
+        }
+    }
+
     /// Kill object.
     pub fn kills(self, object: Object) -> Action {
         Action::Do {
             subject: self.clone(), verb: Verb::Kill, object: object.clone(),
             decorate: vec![
-                (self, Adjective::Murderer.into()),
                 (object, Adjective::Dead.into()),
             ],
             remove: vec![],
@@ -81,6 +97,21 @@ impl Object {
         }
     }
 
+    /// Does something to object, using any verb and no constraints.
+    ///
+    /// This is useful for custom verbs.
+    pub fn does(self, verb: Verb, object: Object) -> Action {
+        Action::Do {
+            subject: self, verb, object,
+            decorate: vec![],
+            remove: vec![],
+            remove_placement: vec![],
+            require: vec![],
+            prevent: vec![],
+            distinct: vec![],
+        }
+    }
+
     /// Opens object.
     pub fn opens(self, object: Object) -> Action {
         Action::Do {
@@ -138,7 +169,6 @@ impl Object {
             subject: self.clone(), verb: Verb::Lock, object: object.clone(),
             decorate: vec![
                 (object.clone(), Adjective::Locked.into()),
-                (object.clone(), Adjective::Closed.into()),
             ],
             remove: vec![
                 (object.clone(), Adjective::Unlocked.into())
@@ -286,7 +316,8 @@ impl Object {
         Action::Do {
             subject: self.clone(), verb: Verb::SleepIn, object: object.clone(),
             decorate: vec![
-                (self.clone(), in_(object.clone()).into())
+                (self.clone(), in_(object.clone()).into()),
+                (self.clone(), Adjective::Asleep.into()),
             ],
             remove: vec![],
             remove_placement: vec![],
@@ -301,11 +332,14 @@ impl Object {
         Action::Do {
             subject: self.clone(), verb: Verb::WakeUpIn, object: object.clone(),
             decorate: vec![
-                (self.clone(), in_(object.clone()).into())
+                (self.clone(), in_(object.clone()).into()),
+                (self.clone(), Adjective::Awake.into()),
             ],
             remove: vec![],
             remove_placement: vec![],
-            require: vec![],
+            require: vec![
+                (self.clone(), Adjective::Asleep.into()),
+            ],
             prevent: vec![],
             distinct: vec![self, object],
         }
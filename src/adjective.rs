@@ -0,0 +1,108 @@
+use *;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Adjective {
+    Dead,
+    Murderer,
+    Open,
+    Closed,
+    Locked,
+    Unlocked,
+    Asleep,
+    Awake,
+    Unconscious,
+    Alive,
+    /// An adjective defined by the user.
+    Custom(Arc<String>),
+}
+
+/// Stores custom adjectives and groups of adjectives that exclude each other.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Adjectives {
+    /// Custom adjectives that sentences can use, e.g. "shiny".
+    pub custom: Vec<Adjective>,
+    /// Groups of adjectives where an object can have at most one member.
+    pub groups: Vec<Vec<Adjective>>,
+}
+
+impl Adjectives {
+    /// Creates adjectives with the built-in exclusion groups.
+    pub fn standard() -> Adjectives {
+        use Adjective::*;
+
+        Adjectives {
+            custom: vec![],
+            groups: vec![
+                vec![Open, Closed],
+                vec![Locked, Unlocked],
+                vec![Dead, Alive],
+                vec![Asleep, Awake, Dead, Unconscious],
+            ],
+        }
+    }
+
+    /// Declares a custom adjective, so sentences can use it.
+    pub fn declare(&mut self, adj: Adjective) {
+        if let Adjective::Custom(_) = adj {
+            if !self.custom.contains(&adj) {self.custom.push(adj)};
+        }
+    }
+
+    /// Declares a group of adjectives where an object can have at most one.
+    ///
+    /// Custom adjectives in the group are declared as well.
+    pub fn exclusive(&mut self, group: Vec<Adjective>) {
+        for adj in &group {self.declare(adj.clone())};
+        if !self.groups.contains(&group) {self.groups.push(group)};
+    }
+
+    /// Finds a custom adjective by its name.
+    pub fn find(&self, word: &str) -> Option<Adjective> {
+        self.custom.iter()
+            .find(|adj| matches!(*adj, Adjective::Custom(ref name) if **name == word))
+            .cloned()
+    }
+
+    /// Returns the adjectives that exclude an adjective.
+    pub fn excluded(&self, adj: &Adjective) -> Vec<Adjective> {
+        let mut res = vec![];
+        for group in &self.groups {
+            if !group.contains(adj) {continue};
+            for other in group {
+                if other != adj && !res.contains(other) {res.push(other.clone())};
+            }
+        }
+        res
+    }
+
+    /// Adds a property to an object.
+    ///
+    /// Adjectives that exclude the property are removed.
+    pub fn decorate(&self, obj: &mut Object, prop: Object) {
+        if let Object::Adj(ref adj) = prop {
+            for other in self.excluded(adj) {
+                obj.remove(&other.into());
+            }
+        }
+        obj.push(prop);
+    }
+
+    /// Returns pairs of adjectives on an object that exclude each other.
+    pub fn contradictions(&self, obj: &Object) -> Vec<(Adjective, Adjective)> {
+        let adjectives: Vec<&Adjective> = match *obj {
+            Object::And(ref list) => list.iter().filter_map(|obj| {
+                if let Object::Adj(ref adj) = *obj {Some(adj)} else {None}
+            }).collect(),
+            Object::Adj(ref adj) => vec![adj],
+            _ => vec![],
+        };
+        let mut res = vec![];
+        for (i, a) in adjectives.iter().enumerate() {
+            let excluded = self.excluded(a);
+            for b in &adjectives[i + 1..] {
+                if excluded.contains(b) {res.push(((*a).clone(), (*b).clone()))};
+            }
+        }
+        res
+    }
+}
@@ -0,0 +1,174 @@
+use *;
+
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// Describes why a bAbI task could not be loaded.
+#[derive(Clone, Debug, PartialEq)]
+pub enum BabiError {
+    /// The file could not be read.
+    Io(String),
+    /// The line does not start with a number.
+    Syntax(usize, String),
+}
+
+impl fmt::Display for BabiError {
+    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BabiError::Io(ref err) => write!(w, "{}", err),
+            BabiError::Syntax(line, ref text) => {
+                write!(w, "line {}: expected a line number in \"{}\"", line, text)
+            }
+        }
+    }
+}
+
+/// A line in a bAbI story.
+#[derive(Clone, Debug, PartialEq)]
+pub enum BabiLine {
+    /// A sentence that tells what happens.
+    Sentence(String),
+    /// A question with the expected answer words.
+    Question(String, Vec<String>),
+}
+
+/// A task in the bAbI format, e.g. one file of the dataset.
+///
+/// Each line starts with a number, which starts over at 1 for a new story.
+/// Questions are followed by a tab, the answer, another tab and the supporting lines:
+///
+/// ```text
+/// 1 Mary moved to the bathroom.
+/// 2 John went to the hallway.
+/// 3 Where is Mary? <TAB>bathroom<TAB>1
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct BabiTask {
+    /// The name of the task.
+    pub name: String,
+    /// The stories with their lines.
+    pub stories: Vec<Vec<BabiLine>>,
+}
+
+/// The result of running a bAbI task.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BabiResult {
+    /// The number of questions answered correctly.
+    pub correct: usize,
+    /// The number of questions.
+    pub total: usize,
+    /// The number of sentences that could not be translated into actions.
+    pub skipped: usize,
+}
+
+impl BabiResult {
+    /// Returns the part of the questions answered correctly.
+    pub fn accuracy(&self) -> f64 {
+        if self.total == 0 {0.0} else {self.correct as f64 / self.total as f64}
+    }
+}
+
+impl BabiTask {
+    /// Parses a task.
+    pub fn parse(name: &str, text: &str) -> Result<BabiTask, BabiError> {
+        let mut stories: Vec<Vec<BabiLine>> = vec![];
+        for (i, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {continue};
+            let syntax = || BabiError::Syntax(i + 1, line.into());
+            let line = line.trim_start();
+            let pos = line.find(' ').ok_or_else(syntax)?;
+            let id: usize = line[..pos].parse().map_err(|_| syntax())?;
+            if id == 1 || stories.is_empty() {stories.push(vec![])};
+            let mut parts = line[pos + 1..].split('\t');
+            let text = parts.next().unwrap_or("").trim().to_string();
+            let story = stories.last_mut().unwrap();
+            match parts.next() {
+                Some(answer) => {
+                    let answer = answer.split(',').map(|w| w.trim().to_lowercase()).collect();
+                    story.push(BabiLine::Question(text, answer));
+                }
+                None => story.push(BabiLine::Sentence(text)),
+            }
+        }
+        Ok(BabiTask {name: name.into(), stories})
+    }
+
+    /// Loads a task from a file, using the file name as the name of the task.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<BabiTask, BabiError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|err| BabiError::Io(format!("{}", err)))?;
+        let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        BabiTask::parse(&name, &text)
+    }
+
+    /// Reads each story into a room and answers its questions.
+    ///
+    /// Objects named in sentences are added to the room when first mentioned.
+    pub fn run(&self) -> BabiResult {
+        let mut result = BabiResult::default();
+        for story in &self.stories {
+            let mut room = Room::new(vec![]);
+            for line in story {
+                match *line {
+                    BabiLine::Sentence(ref text) => {
+                        if !tell(&mut room, text) {result.skipped += 1};
+                    }
+                    BabiLine::Question(ref text, ref expected) => {
+                        result.total += 1;
+                        let words = room.ask(text).ok().and_then(|answer| answer_words(&answer));
+                        if words.as_ref() == Some(expected) {result.correct += 1};
+                    }
+                }
+            }
+        }
+        result
+    }
+}
+
+/// Executes a sentence of a story, returning `false` if it could not be understood.
+fn tell(room: &mut Room, text: &str) -> bool {
+    let text = text.trim_end_matches('.').trim();
+    let text = text.strip_suffix(" there").unwrap_or(text);
+    let action = match parse_action(text) {
+        Ok(action) => action,
+        Err(_) => return false,
+    };
+    // Add one missing object per participant at most.
+    for _ in 0..3 {
+        match room.action(&action) {
+            Ok(()) => return true,
+            Err(ActionError::NotFound(obj)) => match obj {
+                Object::Called(_) | Object::OfType(_) => room.objects.push(obj),
+                _ => return false,
+            },
+            Err(_) => return false,
+        }
+    }
+    false
+}
+
+/// Returns the words of an answer in the form used by bAbI, e.g. "bathroom" or "yes".
+///
+/// Returns `None` when the room does not know the answer, which is scored as wrong.
+/// The room only answers "yes" or "no", so an expected "maybe" is never matched.
+fn answer_words(answer: &Answer) -> Option<Vec<String>> {
+    fn noun(obj: &Object) -> String {
+        let props: &[Object] = match *obj {
+            Object::And(ref list) => list,
+            _ => std::slice::from_ref(obj),
+        };
+        props.iter().find_map(|p| match *p {
+            Object::Called(ref name) | Object::OfType(ref name) => Some(name.to_lowercase()),
+            _ => None,
+        }).unwrap_or_else(|| format!("{}", obj).to_lowercase())
+    }
+
+    Some(match *answer {
+        Answer::Yes => vec!["yes".into()],
+        Answer::No => vec!["no".into()],
+        Answer::Unknown => return None,
+        Answer::Objects(ref objs) => objs.iter().map(noun).collect(),
+        Answer::Places(ref places) => places.iter().map(|p| noun(p.obj_ref())).collect(),
+    })
+}
@@ -0,0 +1,181 @@
+//! Runs scenario files.
+//!
+//! Usage: `room <file or directory>...`
+//!
+//! Directories are searched for files with the `.room` extension.
+//!
+//! Usage: `room babi <file or directory>...`
+//!
+//! Runs tasks in the bAbI format and reports accuracy per task.
+//! Directories are searched for files with the `.txt` extension.
+//!
+//! Usage: `room generate [--seed <n>] [--rooms <n>] [--steps <n>] [--csv] [--prose]`
+//!
+//! Writes records of random actions in random rooms as JSON lines, or CSV.
+//! With `--prose`, the rooms are described in English as well.
+//!
+//! Usage: `room ablate <file or directory>...`
+//!
+//! Runs scenarios without each constraint of the stock actions,
+//! writing a Markdown table of the expectations that break.
+
+extern crate advancedresearch_room as room;
+
+use room::{ablate, ablation_table, BabiTask, Generator, Record, Scenario};
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+fn files(path: &Path, extension: &str) -> Vec<PathBuf> {
+    if path.is_dir() {
+        let mut res: Vec<PathBuf> = fs::read_dir(path)
+            .map(|dir| dir.filter_map(|entry| entry.ok()).map(|entry| entry.path()).collect())
+            .unwrap_or_default();
+        res.retain(|p| p.extension().map(|ext| ext == extension).unwrap_or(false));
+        res.sort();
+        res
+    } else {
+        vec![path.into()]
+    }
+}
+
+fn babi(args: &[String]) {
+    let mut correct = 0;
+    let mut total = 0;
+    for arg in args {
+        for file in files(Path::new(arg), "txt") {
+            match BabiTask::load(&file) {
+                Ok(task) => {
+                    let result = task.run();
+                    println!("{}: {:.1}% ({}/{}), {} sentences skipped", task.name,
+                             100.0 * result.accuracy(), result.correct, result.total, result.skipped);
+                    correct += result.correct;
+                    total += result.total;
+                }
+                Err(err) => {
+                    println!("{}: {}", file.display(), err);
+                    process::exit(1);
+                }
+            }
+        }
+    }
+    println!("{}/{} questions answered correctly", correct, total);
+}
+
+fn generate(args: &[String]) {
+    let mut generator = Generator::new(0);
+    let mut csv = false;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--csv" {
+            csv = true;
+            continue;
+        }
+        if arg == "--prose" {
+            generator.prose = true;
+            continue;
+        }
+        let value = args.next().and_then(|v| v.parse::<u64>().ok());
+        match (&**arg, value) {
+            ("--seed", Some(n)) => generator.seed = n,
+            ("--rooms", Some(n)) => generator.rooms = n as usize,
+            ("--steps", Some(n)) => generator.steps = n as usize,
+            _ => {
+                eprintln!("Usage: room generate [--seed <n>] [--rooms <n>] [--steps <n>] [--csv] [--prose]");
+                process::exit(2);
+            }
+        }
+    }
+    if csv {
+        if generator.prose {
+            println!("{},{}", Record::CSV_HEADER, Record::CSV_PROSE_HEADER);
+        } else {
+            println!("{}", Record::CSV_HEADER);
+        }
+    }
+    for record in generator.generate() {
+        println!("{}", if csv {record.to_csv()} else {record.to_json()});
+    }
+}
+
+fn ablation(args: &[String]) {
+    let mut scenarios = vec![];
+    for arg in args {
+        for file in files(Path::new(arg), "room") {
+            let scenario = fs::read_to_string(&file)
+                .map_err(|err| format!("{}", err))
+                .and_then(|text| Scenario::parse(&text).map_err(|err| format!("{}", err)));
+            match scenario {
+                Ok(scenario) => {
+                    let name = file.file_name().map(|n| n.to_string_lossy().into_owned());
+                    scenarios.push((name.unwrap_or_default(), scenario));
+                }
+                Err(err) => {
+                    println!("{}: {}", file.display(), err);
+                    process::exit(1);
+                }
+            }
+        }
+    }
+    print!("{}", ablation_table(&ablate(&scenarios)));
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.is_empty() {
+        eprintln!("Usage: room <file or directory>...");
+        eprintln!("       room babi <file or directory>...");
+        eprintln!("       room generate [--seed <n>] [--rooms <n>] [--steps <n>] [--csv] [--prose]");
+        eprintln!("       room ablate <file or directory>...");
+        process::exit(2);
+    }
+    if args[0] == "ablate" {
+        ablation(&args[1..]);
+        return;
+    }
+    if args[0] == "babi" {
+        babi(&args[1..]);
+        return;
+    }
+    if args[0] == "generate" {
+        generate(&args[1..]);
+        return;
+    }
+
+    let mut passed = 0;
+    let mut failed = 0;
+    for arg in &args {
+        for file in files(Path::new(arg), "room") {
+            let text = match fs::read_to_string(&file) {
+                Ok(text) => text,
+                Err(err) => {
+                    println!("FAIL {}: {}", file.display(), err);
+                    failed += 1;
+                    continue;
+                }
+            };
+            match Scenario::parse(&text) {
+                Ok(scenario) => {
+                    let failures = scenario.run();
+                    if failures.is_empty() {
+                        println!("PASS {}", file.display());
+                        passed += 1;
+                    } else {
+                        println!("FAIL {}", file.display());
+                        for failure in &failures {
+                            println!("    {}", failure);
+                        }
+                        failed += 1;
+                    }
+                }
+                Err(err) => {
+                    println!("FAIL {}: {}", file.display(), err);
+                    failed += 1;
+                }
+            }
+        }
+    }
+    println!("{} passed, {} failed", passed, failed);
+    if failed > 0 {process::exit(1)};
+}
@@ -0,0 +1,53 @@
+use *;
+
+/// Describes a violated invariant of a room.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Inconsistency {
+    /// An object has adjectives that exclude each other.
+    Exclusive(usize, Adjective, Adjective),
+    /// An object both has and has not something.
+    HasAndHasNot(usize, Object),
+    /// An object is on or in more than one place.
+    Placements(usize, Vec<Placement>),
+    /// An object matches both patterns of an invariant in `Room::invariants`.
+    Incompatible(usize, Object, Object),
+}
+
+impl Room {
+    /// Returns all violated invariants in the room.
+    pub fn check_consistency(&self) -> Vec<Inconsistency> {
+        let mut res = vec![];
+        for (i, obj) in self.objects.iter().enumerate() {
+            for (a, b) in self.adjectives.contradictions(obj) {
+                res.push(Inconsistency::Exclusive(i, a, b));
+            }
+
+            let props: &[Object] = if let Object::And(ref list) = *obj {list} else {&[]};
+            for prop in props {
+                if let Object::Has(ref a) = *prop {
+                    if props.iter().any(|p| matches!(p, Object::HasNot(b) if a.matches(b))) {
+                        res.push(Inconsistency::HasAndHasNot(i, (**a).clone()));
+                    }
+                }
+            }
+
+            let places: Vec<Placement> = props.iter().filter_map(|p| match *p {
+                Object::Placement(ref place) => match **place {
+                    Placement::On(_) | Placement::In(_) => Some((**place).clone()),
+                    _ => None,
+                },
+                _ => None,
+            }).collect();
+            if places.len() > 1 {
+                res.push(Inconsistency::Placements(i, places));
+            }
+
+            for (a, b) in &self.invariants {
+                if self.taxonomy.matches(obj, a) && self.taxonomy.matches(obj, b) {
+                    res.push(Inconsistency::Incompatible(i, a.clone(), b.clone()));
+                }
+            }
+        }
+        res
+    }
+}
@@ -0,0 +1,309 @@
+use *;
+
+/// Generates labelled records of actions in random rooms.
+///
+/// The same seed always generates the same records.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Generator {
+    /// The seed of the random number generator.
+    pub seed: u64,
+    /// The number of rooms to generate.
+    pub rooms: usize,
+    /// The number of actions to try in each room.
+    pub steps: usize,
+    /// Whether to describe the rooms in English as well.
+    pub prose: bool,
+}
+
+/// An action tried in a room, with the outcome.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Record {
+    /// The index of the room.
+    pub room: usize,
+    /// The index of the action in the room.
+    pub step: usize,
+    /// The objects in the room before the action.
+    pub before: Vec<Object>,
+    /// The action.
+    pub action: Action,
+    /// Why the action failed, or `None` if it succeeded.
+    pub error: Option<ActionError>,
+    /// The objects in the room after the action.
+    pub after: Vec<Object>,
+    /// The room before and after the action in English, if the generator describes rooms.
+    pub prose: Option<(String, String)>,
+}
+
+/// A small random number generator (SplitMix64), to avoid dependencies.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+impl Generator {
+    /// Creates a new generator with a seed.
+    pub fn new(seed: u64) -> Generator {
+        Generator {seed, rooms: 10, steps: 5, prose: false}
+    }
+
+    /// Generates records.
+    pub fn generate(&self) -> Vec<Record> {
+        let mut rng = Rng(self.seed);
+        let mut res = vec![];
+        for i in 0..self.rooms {
+            let mut room = random_room(&mut rng);
+            for step in 0..self.steps {
+                let action = match random_action(&mut rng, &room) {
+                    Some(action) => action,
+                    None => break,
+                };
+                let before = room.objects.clone();
+                let before_text = if self.prose {Some(room.describe())} else {None};
+                let error = room.action(&action).err();
+                let after = room.objects.clone();
+                let prose = before_text.map(|before| (before, room.describe()));
+                res.push(Record {room: i, step, before, action, error, after, prose});
+            }
+        }
+        res
+    }
+}
+
+fn random_room(rng: &mut Rng) -> Room {
+    use Adjective::*;
+    use Object::*;
+
+    let mut objects = vec![I];
+    for &(name, ref pronoun) in &[("Peter", He), ("Sheila", She), ("John", He)] {
+        if rng.below(3) == 0 {continue};
+        let mut agent = And(vec![called(name), pronoun.clone()]);
+        match rng.below(10) {
+            0 => agent.push(Dead.into()),
+            1 | 2 => agent.push(Asleep.into()),
+            _ => {}
+        }
+        objects.push(agent);
+    }
+    let mut door = And(vec![of_type("door")]);
+    match rng.below(3) {
+        0 => door.push(Open.into()),
+        1 => door.push(Unlocked.into()),
+        _ => door.push(Locked.into()),
+    }
+    objects.push(door);
+    objects.push(key_to(of_type("door")));
+    objects.push(And(vec![of_type("ladder"), on(of_type("ground")).into()]));
+    objects.push(of_type("ground"));
+    objects.push(of_type("roof"));
+    objects.push(of_type("bed"));
+    let mut room = Room::new(objects);
+    room.apply_rules();
+    room
+}
+
+fn random_action(rng: &mut Rng, room: &Room) -> Option<Action> {
+    let participants: Vec<Object> = (0..room.objects.len()).filter_map(|i| room.refer(i)).collect();
+    let agents: Vec<Object> = (0..room.objects.len())
+        .filter(|&i| room.features(i).map(|f| f.contains(&Feature::Animate)).unwrap_or(false))
+        .filter_map(|i| room.refer(i))
+        .collect();
+    if participants.is_empty() || agents.is_empty() {return None};
+
+    // Most actions are done by agents, but some test the restrictions.
+    let a = if rng.below(5) == 0 {&participants} else {&agents};
+    let a = a[rng.below(a.len())].clone();
+    let b = participants[rng.below(participants.len())].clone();
+    let c = participants[rng.below(participants.len())].clone();
+    Some(match rng.below(18) {
+        0 => a.opens(b),
+        1 => a.closes(b),
+        2 => a.locks(b),
+        3 => a.unlocks(b),
+        4 => a.picks_up(b),
+        5 => a.puts_down(b),
+        6 => a.kills(b),
+        7 => a.talk_to(b),
+        8 => a.walks_through(b),
+        9 => a.carries(b),
+        10 => a.sleeps_in(b),
+        11 => a.wakes_up_in(b),
+        12 => a.goes_to(b),
+        13 => a.climbs_to(b, on(c)),
+        14 => a.gives_item(b, c),
+        15 => a.moves(b, on(c)),
+        16 => a.stands_on(b),
+        _ => a.leans_toward(b),
+    })
+}
+
+/// Returns the name of the kind of error, e.g. "Prevent".
+fn error_kind(err: &ActionError) -> &'static str {
+    use ActionError::*;
+
+    match *err {
+        NotFound(_) => "NotFound",
+        Ambiguous(_, _) => "Ambiguous",
+        Distinct(_, _) => "Distinct",
+        Require(_, _) => "Require",
+        Prevent(_, _) => "Prevent",
+        CannotAct(_, _) => "CannotAct",
+        Unresponsive(_, _) => "Unresponsive",
+        Restricted(_, _) => "Restricted",
+        Inconsistent(_) => "Inconsistent",
+        CommonSense(_) => "CommonSense",
+    }
+}
+
+fn json_string(text: &str) -> String {
+    let mut res = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => res.push_str("\\\""),
+            '\\' => res.push_str("\\\\"),
+            '\n' => res.push_str("\\n"),
+            '\t' => res.push_str("\\t"),
+            c if (c as u32) < 0x20 => res.push_str(&format!("\\u{:04x}", c as u32)),
+            c => res.push(c),
+        }
+    }
+    res.push('"');
+    res
+}
+
+/// Writes an object as JSON, with variants tagged by name like `{"Called":"Peter"}`.
+fn json_object(obj: &Object) -> String {
+    use Object::*;
+
+    let tagged = |tag: &str, value: String| format!("{{{}:{}}}", json_string(tag), value);
+    match *obj {
+        I | You | He | She | It | That | Any => json_string(&format!("{:?}", obj)),
+        And(ref list) => tagged("And", json_objects(list)),
+        Placement(ref place) => {
+            let tag = match **place {
+                ::Placement::On(_) => "On",
+                ::Placement::LeanToward(_) => "LeanToward",
+                ::Placement::In(_) => "In",
+                ::Placement::OutOf(_) => "OutOf",
+            };
+            tagged(tag, json_object(place.obj_ref()))
+        }
+        Role(ref role) => {
+            let ::Role::OpponentOf(ref obj) = **role;
+            tagged("OpponentOf", json_object(obj))
+        }
+        Has(ref obj) => tagged("Has", json_object(obj)),
+        HasNot(ref obj) => tagged("HasNot", json_object(obj)),
+        KeyTo(ref obj) => tagged("KeyTo", json_object(obj)),
+        Called(ref name) => tagged("Called", json_string(name)),
+        OfType(ref name) => tagged("OfType", json_string(name)),
+        Var(ref name) => tagged("Var", json_string(name)),
+        Adj(Adjective::Custom(ref name)) => tagged("Adj", json_string(name)),
+        Adj(ref adj) => tagged("Adj", json_string(&format!("{:?}", adj))),
+        WasBy(ref verb, ref obj) => {
+            tagged("WasBy", format!("[{},{}]", json_string(verb.name()), json_object(obj)))
+        }
+        DidTo(ref verb, ref obj) => {
+            tagged("DidTo", format!("[{},{}]", json_string(verb.name()), json_object(obj)))
+        }
+        Id(id) => tagged("Id", format!("{}", id.0)),
+    }
+}
+
+/// Writes a list of objects as a JSON array.
+fn json_objects(objs: &[Object]) -> String {
+    let items: Vec<String> = objs.iter().map(json_object).collect();
+    format!("[{}]", items.join(","))
+}
+
+/// Writes an action as JSON with its participants, e.g.
+/// `{"subject":"I","verb":"Give","object":{"OfType":"door"},"other":{"KeyTo":{"OfType":"door"}}}`.
+///
+/// The third participant is the item given, the opponent played against,
+/// or the place something is moved or climbed to, and `null` for other verbs.
+fn json_action(action: &Action) -> String {
+    let Action::Do {ref subject, ref verb, ref object, ref decorate, ref distinct, ..} = *action;
+    let other = match *verb {
+        Verb::Give | Verb::Play | Verb::Climb => distinct.get(2),
+        Verb::Move => decorate.iter()
+            .find(|&(obj, prop)| obj == object && matches!(prop, Object::Placement(_)))
+            .map(|(_, prop)| prop),
+        _ => None,
+    };
+    format!("{{\"subject\":{},\"verb\":{},\"object\":{},\"other\":{}}}",
+            json_object(subject), json_string(verb.name()), json_object(object),
+            other.map(json_object).unwrap_or_else(|| "null".into()))
+}
+
+fn csv_field(text: &str) -> String {
+    format!("\"{}\"", text.replace('"', "\"\""))
+}
+
+impl Record {
+    /// The header line of CSV files.
+    ///
+    /// The rooms before and after are written as JSON arrays of objects,
+    /// and the action as a JSON object with its participants, followed by the action in English.
+    pub const CSV_HEADER: &'static str =
+        "room,step,before,action,action_text,verb,success,error,reason,after";
+
+    /// The extra columns of CSV files when the generator describes rooms in English.
+    pub const CSV_PROSE_HEADER: &'static str = "before_text,after_text";
+
+    /// Returns the name of the verb, the kind of error and the reason.
+    fn fields(&self) -> (String, &'static str, String) {
+        let Action::Do {ref verb, ..} = self.action;
+        let kind = self.error.as_ref().map(error_kind).unwrap_or("");
+        let reason = self.error.as_ref().map(|err| format!("{}", err)).unwrap_or_default();
+        (verb.name().into(), kind, reason)
+    }
+
+    /// Writes the record as a line of JSON.
+    ///
+    /// The rooms in English are added as `before_text` and `after_text` when present.
+    pub fn to_json(&self) -> String {
+        let (verb, kind, reason) = self.fields();
+        let (error, reason) = if self.error.is_some() {
+            (json_string(kind), json_string(&reason))
+        } else {
+            ("null".into(), "null".into())
+        };
+        let prose = match self.prose {
+            Some((ref before, ref after)) => {
+                format!(",\"before_text\":{},\"after_text\":{}", json_string(before), json_string(after))
+            }
+            None => String::new(),
+        };
+        format!("{{\"room\":{},\"step\":{},\"before\":{},\"action\":{},\"action_text\":{},\
+                 \"verb\":{},\"success\":{},\"error\":{},\"reason\":{},\"after\":{}{}}}",
+                self.room, self.step, json_objects(&self.before), json_action(&self.action),
+                json_string(&format!("{}", self.action)), json_string(&verb),
+                self.error.is_none(), error, reason, json_objects(&self.after), prose)
+    }
+
+    /// Writes the record as a line of CSV, with columns as in `Record::CSV_HEADER`,
+    /// followed by the columns in `Record::CSV_PROSE_HEADER` when the rooms are in English.
+    pub fn to_csv(&self) -> String {
+        let (verb, kind, reason) = self.fields();
+        let prose = match self.prose {
+            Some((ref before, ref after)) => format!(",{},{}", csv_field(before), csv_field(after)),
+            None => String::new(),
+        };
+        format!("{},{},{},{},{},{},{},{},{},{}{}",
+                self.room, self.step, csv_field(&json_objects(&self.before)),
+                csv_field(&json_action(&self.action)),
+                csv_field(&format!("{}", self.action)), csv_field(&verb),
+                self.error.is_none(), csv_field(kind), csv_field(&reason),
+                csv_field(&json_objects(&self.after)), prose)
+    }
+}
@@ -0,0 +1,65 @@
+use *;
+
+/// A question asking which of several objects a description refers to.
+///
+/// Created with `Room::clarify` and rendered in English with `Display`,
+/// e.g. "Which door: the locked one or the open one?".
+#[derive(Clone, Debug, PartialEq)]
+pub struct Clarification {
+    /// The ambiguous description.
+    pub description: Object,
+    /// The candidates with the properties that tell them apart.
+    pub candidates: Vec<(usize, Object)>,
+}
+
+impl Room {
+    /// Creates a question when an action failed because of an ambiguous description.
+    pub fn clarify(&self, err: &ActionError) -> Option<Clarification> {
+        if let ActionError::Ambiguous(ref description, ref candidates) = *err {
+            let known: &[Object] = match *description {
+                Object::And(ref list) => list,
+                ref obj => std::slice::from_ref(obj),
+            };
+            let candidates = candidates.iter().map(|&ind| {
+                let pattern = self.refer(ind).unwrap_or_else(|| self.objects[ind].clone());
+                let mut props: Vec<Object> = match pattern {
+                    Object::And(list) => list,
+                    obj => vec![obj],
+                };
+                props.retain(|p| !known.contains(p));
+                let props = if props.len() == 1 {props.pop().unwrap()} else {Object::And(props)};
+                (ind, props)
+            }).collect();
+            Some(Clarification {description: description.clone(), candidates})
+        } else {
+            None
+        }
+    }
+}
+
+impl Clarification {
+    /// Narrows the candidates with an answer, e.g. `Locked.into()`.
+    ///
+    /// Returns `Err(ActionError::Ambiguous(..))` with the remaining candidates
+    /// if the answer does not pick out a single one.
+    pub fn answer(&self, room: &Room, reply: &Object) -> Result<usize, ActionError> {
+        let left: Vec<usize> = self.candidates.iter()
+            .map(|&(ind, _)| ind)
+            .filter(|&ind| room.taxonomy.matches(&room.objects[ind], reply))
+            .collect();
+        match left.len() {
+            0 => Err(ActionError::NotFound(reply.clone())),
+            1 => Ok(left[0]),
+            _ => Err(ActionError::Ambiguous(self.description.clone(), left)),
+        }
+    }
+
+    /// Replaces the ambiguous description in an action with one picked by the answer.
+    pub fn resolve(&self, room: &Room, action: &Action, reply: &Object) -> Result<Action, ActionError> {
+        let ind = self.answer(room, reply)?;
+        let description = room.refer(ind).ok_or_else(|| {
+            ActionError::Ambiguous(self.description.clone(), vec![ind])
+        })?;
+        Ok(action.replace(&self.description, &description))
+    }
+}
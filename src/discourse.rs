@@ -0,0 +1,76 @@
+use *;
+
+/// Tracks recently mentioned objects to resolve pronouns.
+///
+/// Pronouns are resolved by gender, animacy and recency,
+/// such that "Peter took the key. He gave it to Sheila." refers to objects in the room.
+/// Gender is taken from the `He` and `She` properties of objects, never from names.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Discourse {
+    /// The ids of mentioned objects, the most recent last.
+    pub mentioned: Vec<ObjectId>,
+}
+
+impl Discourse {
+    /// Creates a new discourse with nothing mentioned.
+    pub fn new() -> Discourse {
+        Discourse {mentioned: vec![]}
+    }
+
+    /// Records that an object was mentioned.
+    pub fn mention(&mut self, id: ObjectId) {
+        self.mentioned.retain(|&i| i != id);
+        self.mentioned.push(id);
+    }
+
+    /// Returns the most recently mentioned object that a pronoun can refer to.
+    ///
+    /// Returns `None` for objects that are not pronouns, and for `I` and `You`.
+    pub fn antecedent(&self, room: &Room, pronoun: &Object) -> Option<usize> {
+        use Object::*;
+
+        let animate = |ind: usize| match room.features(ind) {
+            Some(features) => features.contains(&Feature::Animate),
+            None => false,
+        };
+        let fits = |ind: usize| {
+            let obj = &room.objects[ind];
+            let (he, she) = (obj.matches(&He), obj.matches(&She));
+            match *pronoun {
+                He => he,
+                She => she,
+                It => !he && !she && !animate(ind),
+                That => true,
+                _ => false,
+            }
+        };
+        self.mentioned.iter().rev().filter_map(|&id| room.index(id)).find(|&ind| fits(ind))
+    }
+
+    /// Replaces pronouns in an action with descriptions of the objects they refer to.
+    ///
+    /// Pronouns without an antecedent are kept,
+    /// such that they are identified directly in the room.
+    pub fn resolve(&self, room: &Room, action: &Action) -> Action {
+        action.substitute(&|obj| {
+            self.antecedent(room, obj).and_then(|ind| room.refer(ind))
+        })
+    }
+
+    /// Resolves pronouns and executes an action,
+    /// recording the participants as mentioned when it succeeds.
+    pub fn action(&mut self, room: &mut Room, action: &Action) -> Result<(), ActionError> {
+        let action = self.resolve(room, action);
+        room.action(&action)?;
+        let Action::Do {ref subject, ref object, ref distinct, ..} = action;
+        let mut participants = vec![subject, object];
+        participants.extend(distinct.iter().filter(|&obj| obj != subject && obj != object));
+        for obj in participants {
+            if let Ok(ind) = room.find(obj) {
+                let id = room.id(ind);
+                self.mention(id);
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,399 @@
+use *;
+
+use std::fmt;
+
+#[derive(Copy, Clone, PartialEq)]
+enum Case {
+    Subject,
+    Object,
+}
+
+fn pronoun(obj: &Object, case: Case) -> Option<&'static str> {
+    use Object::*;
+
+    Some(match (obj, case) {
+        (I, Case::Subject) => "I",
+        (I, Case::Object) => "me",
+        (You, _) => "you",
+        (He, Case::Subject) => "he",
+        (He, Case::Object) => "him",
+        (She, Case::Subject) => "she",
+        (She, Case::Object) => "her",
+        (It, _) => "it",
+        (That, _) => "that",
+        (Any, _) => "something",
+        _ => return None,
+    })
+}
+
+fn properties(obj: &Object) -> &[Object] {
+    if let Object::And(ref list) = *obj {list} else {std::slice::from_ref(obj)}
+}
+
+/// Picks the property used as the head of a noun phrase.
+fn head(props: &[Object]) -> Option<&Object> {
+    props.iter().find(|p| matches!(p, Object::Called(_) | Object::Var(_)))
+        .or_else(|| props.iter().find(|p| pronoun(p, Case::Subject).is_some()))
+        .or_else(|| props.iter().find(|p| matches!(p, Object::OfType(_) | Object::KeyTo(_))))
+}
+
+fn be(head: Option<&Object>) -> &'static str {
+    match head {
+        Some(&Object::I) => "am",
+        Some(&Object::You) => "are",
+        _ => "is",
+    }
+}
+
+fn have(head: Option<&Object>) -> &'static str {
+    match head {
+        Some(&Object::I) | Some(&Object::You) => "have",
+        _ => "has",
+    }
+}
+
+fn does(head: Option<&Object>) -> &'static str {
+    match head {
+        Some(&Object::I) | Some(&Object::You) => "do",
+        _ => "does",
+    }
+}
+
+/// Joins words as an English list, e.g. "a, b and c".
+fn join(items: &[String]) -> String {
+    match items.len() {
+        0 => String::new(),
+        1 => items[0].clone(),
+        n => format!("{} and {}", items[..n - 1].join(", "), items[n - 1]),
+    }
+}
+
+/// Joins words as English alternatives, e.g. "a, b or c".
+fn either(items: &[String]) -> String {
+    match items.len() {
+        0 => String::new(),
+        1 => items[0].clone(),
+        n => format!("{} or {}", items[..n - 1].join(", "), items[n - 1]),
+    }
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(c) => c.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Renders a property as a verb phrase about the head, e.g. "was killed by Sheila".
+fn predicate(prop: &Object, head: Option<&Object>) -> Option<String> {
+    use Object::*;
+
+    Some(match *prop {
+        Adj(Adjective::Murderer) => format!("{} a murderer", be(head)),
+        Adj(ref adj) => format!("{} {}", be(head), adj),
+        Placement(ref place) => format!("{} {}", be(head), place),
+        Role(ref role) => format!("{} {}", be(head), role),
+        Has(ref obj) => format!("{} {}", have(head), noun_phrase(obj, Case::Object)),
+        HasNot(ref obj) => format!("{} not have {}", does(head), noun_phrase(obj, Case::Object)),
+        WasBy(ref verb, ref obj) => format!("{} {} by {}",
+            if head == Some(&You) {"were"} else {"was"},
+            verb.participle(), noun_phrase(obj, Case::Object)),
+        DidTo(ref verb, ref obj) => past(verb, obj),
+        OfType(ref ty) => format!("{} a {}", be(head), ty),
+        Called(ref name) => format!("{} called {}", be(head), name),
+        KeyTo(_) | And(_) => format!("{} {}", be(head), noun_phrase(prop, Case::Object)),
+        Var(ref name) => format!("{} {}", be(head), name),
+        I | You | He | She | It | That | Any | Id(_) => return None,
+    })
+}
+
+/// Renders a verb in past tense with its object, e.g. "picked up the key".
+///
+/// The particle of a phrasal verb goes after a pronoun, e.g. "picked it up".
+fn past(verb: &Verb, obj: &Object) -> String {
+    let o = noun_phrase(obj, Case::Object);
+    match (verb, verb.past().rsplit_once(' ')) {
+        (Verb::PickUp, Some((past, particle))) | (Verb::PutDown, Some((past, particle)))
+        if pronoun(obj, Case::Object).is_some() => format!("{} {} {}", past, o, particle),
+        _ => format!("{} {}", verb.past(), o),
+    }
+}
+
+fn noun_phrase(obj: &Object, case: Case) -> String {
+    use Object::*;
+
+    let props = properties(obj);
+    let head = head(props);
+    let others = props.iter().filter(|p| Some(*p) != head);
+    match head {
+        Some(Called(name)) | Some(Var(name)) => {
+            let preds: Vec<String> = others.filter_map(|p| predicate(p, head)).collect();
+            if preds.is_empty() {format!("{}", name)}
+            else {format!("{}, who {}", name, join(&preds))}
+        }
+        Some(h) if pronoun(h, case).is_some() => {
+            let base = pronoun(h, case).unwrap();
+            let preds: Vec<String> = others.filter_map(|p| predicate(p, head)).collect();
+            if preds.is_empty() {base.into()}
+            else {format!("{}, who {}", base, join(&preds))}
+        }
+        _ => {
+            let mut adjectives = vec![];
+            let mut clauses = vec![];
+            for prop in others {
+                match *prop {
+                    Adj(ref adj) if *adj != Adjective::Murderer => {
+                        adjectives.push(format!("{}", adj));
+                    }
+                    WasBy(ref verb, ref by) => {
+                        clauses.push(format!("{} {}", noun_phrase(by, Case::Subject), verb.past()));
+                    }
+                    _ => clauses.extend(predicate(prop, None)),
+                }
+            }
+            let noun = match head {
+                Some(OfType(ty)) => format!("{}", ty),
+                Some(KeyTo(obj)) => format!("key to {}", noun_phrase(obj, Case::Object)),
+                _ => "one".into(),
+            };
+            let mut res = String::from("the ");
+            if !adjectives.is_empty() {
+                res.push_str(&adjectives.join(", "));
+                res.push(' ');
+            }
+            res.push_str(&noun);
+            if !clauses.is_empty() {
+                res.push_str(" that ");
+                res.push_str(&clauses.join(" and that "));
+            }
+            res
+        }
+    }
+}
+
+/// Renders an object with a property as a statement, e.g. "the door is locked".
+pub fn statement(obj: &Object, prop: &Object) -> String {
+    let head = head(properties(obj));
+    let pred = predicate(prop, head)
+        .unwrap_or_else(|| format!("{} {}", be(head), noun_phrase(prop, Case::Object)));
+    format!("{} {}", noun_phrase(obj, Case::Subject), pred)
+}
+
+/// Renders a placement as a destination of movement, e.g. "onto the roof".
+fn motion(place: &Placement) -> String {
+    use Placement::*;
+
+    match *place {
+        On(ref obj) => format!("onto {}", noun_phrase(obj, Case::Object)),
+        LeanToward(ref obj) => format!("toward {}", noun_phrase(obj, Case::Object)),
+        In(ref obj) => format!("into {}", noun_phrase(obj, Case::Object)),
+        OutOf(ref obj) => format!("out of {}", noun_phrase(obj, Case::Object)),
+    }
+}
+
+impl fmt::Display for Adjective {
+    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        use Adjective::*;
+
+        w.write_str(match *self {
+            Dead => "dead",
+            Murderer => "murderer",
+            Open => "open",
+            Closed => "closed",
+            Locked => "locked",
+            Unlocked => "unlocked",
+            Asleep => "asleep",
+            Awake => "awake",
+            Unconscious => "unconscious",
+            Alive => "alive",
+            Custom(ref name) => name,
+        })
+    }
+}
+
+impl fmt::Display for Feature {
+    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        use Feature::*;
+
+        w.write_str(match *self {
+            Animate => "animate",
+            Openable => "openable",
+            Lockable => "lockable",
+            Carriable => "carriable",
+            Climbable => "climbable",
+            Custom(ref name) => name,
+        })
+    }
+}
+
+impl fmt::Display for Verb {
+    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        w.write_str(self.base())
+    }
+}
+
+impl fmt::Display for Placement {
+    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        use Placement::*;
+
+        let (prep, obj) = match *self {
+            On(ref obj) => ("on", obj),
+            LeanToward(ref obj) => ("leaning toward", obj),
+            In(ref obj) => ("in", obj),
+            OutOf(ref obj) => ("out of", obj),
+        };
+        write!(w, "{} {}", prep, noun_phrase(obj, Case::Object))
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Role::OpponentOf(ref obj) => {
+                write!(w, "the opponent of {}", noun_phrase(obj, Case::Object))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Object {
+    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        w.write_str(&noun_phrase(self, Case::Subject))
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        let Action::Do {ref subject, ref verb, ref object, ref decorate, ..} = *self;
+        let find = |target: &Object| decorate.iter()
+            .find(|&(obj, _)| obj == target)
+            .map(|(_, prop)| prop);
+        let s = noun_phrase(subject, Case::Subject);
+        let o = noun_phrase(object, Case::Object);
+        let sentence = match (verb, find(subject), find(object)) {
+            (Verb::Give, _, Some(Object::Has(item))) => {
+                format!("{} gave {} to {}", s, noun_phrase(item, Case::Object), o)
+            }
+            (Verb::Move, _, Some(Object::Placement(place))) => {
+                format!("{} moved {} {}", s, o, motion(place))
+            }
+            (Verb::Climb, Some(Object::Placement(place)), _) => {
+                if place.obj_ref() == object {format!("{} climbed {}", s, motion(place))}
+                else {format!("{} climbed {} {}", s, o, motion(place))}
+            }
+            (Verb::Play, Some(Object::Role(role)), _) => {
+                format!("{} played {} against {}", s, o,
+                        noun_phrase(role.obj_ref(), Case::Object))
+            }
+            _ => format!("{} {}", s, past(verb, object)),
+        };
+        write!(w, "{}.", capitalize(&sentence))
+    }
+}
+
+impl fmt::Display for ActionError {
+    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        use ActionError::*;
+
+        match *self {
+            NotFound(ref obj) => {
+                let np = noun_phrase(obj, Case::Object);
+                write!(w, "There is no {}.", np.trim_start_matches("the "))
+            }
+            Ambiguous(ref obj, ref candidates) => {
+                write!(w, "It is unclear which of {} objects is {}.",
+                       candidates.len(), noun_phrase(obj, Case::Object))
+            }
+            Distinct(i, j) => {
+                write!(w, "Objects {} and {} in the distinct group are the same.", i, j)
+            }
+            Require(ref obj, ref prop) => {
+                write!(w, "It is required that {}.", statement(obj, prop))
+            }
+            Prevent(ref obj, ref prop) => {
+                write!(w, "It is prevented because {}.", statement(obj, prop))
+            }
+            CannotAct(ref obj, ref pattern) => {
+                write!(w, "{} can not act because {}.", capitalize(&format!("{}", obj)),
+                       statement(obj, pattern))
+            }
+            Restricted(ref obj, ref restriction) => {
+                let head = head(properties(obj));
+                let (can_not, feature) = match *restriction {
+                    Restriction::Subject(ref verb, ref feature) => (verb.base().to_string(), feature),
+                    Restriction::Object(ref verb, ref feature) => (format!("be {}", verb.participle()), feature),
+                };
+                write!(w, "{} can not {} because {} {} not {}.", capitalize(&format!("{}", obj)),
+                       can_not, noun_phrase(obj, Case::Subject), be(head), feature)
+            }
+            Unresponsive(ref obj, ref pattern) => {
+                write!(w, "{} can not respond because {}.", capitalize(&format!("{}", obj)),
+                       statement(obj, pattern))
+            }
+            Inconsistent(_) => write!(w, "It makes the room inconsistent."),
+            CommonSense(ref warnings) => {
+                let goals: Vec<String> = warnings.iter().map(|w| format!("{}", w.goal.name)).collect();
+                write!(w, "It makes it impossible to {}.", join(&goals))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Answer::Yes => write!(w, "Yes."),
+            Answer::No => write!(w, "No."),
+            Answer::Unknown => write!(w, "I do not know."),
+            Answer::Objects(ref objs) => {
+                let objs: Vec<String> = objs.iter().map(|obj| noun_phrase(obj, Case::Subject)).collect();
+                write!(w, "{}.", capitalize(&join(&objs)))
+            }
+            Answer::Places(ref places) => {
+                let places: Vec<String> = places.iter().map(|place| format!("{}", place)).collect();
+                write!(w, "{}.", capitalize(&join(&places)))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Clarification {
+    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        let options: Vec<String> = self.candidates.iter()
+            .map(|(_, props)| noun_phrase(props, Case::Object))
+            .collect();
+        let props = properties(&self.description);
+        match head(props) {
+            Some(&Object::OfType(_)) | Some(&Object::KeyTo(_)) => {
+                let np = noun_phrase(&self.description, Case::Object);
+                write!(w, "Which {}: {}?", np.trim_start_matches("the "), either(&options))
+            }
+            _ => write!(w, "Who do you mean: {}?", either(&options)),
+        }
+    }
+}
+
+impl Room {
+    /// Describes the room as an English paragraph.
+    pub fn describe(&self) -> String {
+        let sentences: Vec<String> = self.objects.iter().map(|obj| {
+            let props = properties(obj);
+            let head = head(props);
+            let subject = match head {
+                Some(h) => noun_phrase(h, Case::Subject),
+                None => "something".into(),
+            };
+            let preds: Vec<String> = props.iter()
+                .filter(|p| Some(*p) != head)
+                .filter_map(|p| predicate(p, head))
+                .collect();
+            if preds.is_empty() {
+                format!("{} {} here.", capitalize(&subject), be(head))
+            } else {
+                format!("{} {}.", capitalize(&subject), join(&preds))
+            }
+        }).collect();
+        sentences.join(" ")
+    }
+}
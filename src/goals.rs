@@ -0,0 +1,102 @@
+use *;
+
+/// A goal that an agent normally expects to be able to achieve.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CommonGoal {
+    /// The name of the goal.
+    pub name: Arc<String>,
+    /// The goal.
+    pub goal: Goal,
+}
+
+impl CommonGoal {
+    /// Creates a new common goal.
+    pub fn new(name: &str, goal: Goal) -> CommonGoal {
+        CommonGoal {name: Arc::new(name.into()), goal}
+    }
+
+    /// Being able to leave through a door.
+    pub fn leave_through(agent: Object, door: Object) -> CommonGoal {
+        CommonGoal::new("leave through the door", Goal::new(vec![
+            (agent, Object::DidTo(Verb::WalkThrough, Box::new(door))),
+        ]))
+    }
+
+    /// Being able to get down from some place to another.
+    pub fn get_down(agent: Object, from: Placement, to: Placement) -> CommonGoal {
+        CommonGoal::new("get down", Goal {
+            require: vec![(agent.clone(), to.into())],
+            prevent: vec![(agent, from.into())],
+        })
+    }
+
+    /// Staying alive.
+    pub fn stay_alive(agent: Object) -> CommonGoal {
+        CommonGoal::new("stay alive", Goal {
+            require: vec![],
+            prevent: vec![(agent, Adjective::Dead.into())],
+        })
+    }
+}
+
+/// Settings for checking actions against common goals.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CommonSense {
+    /// The goals that actions should not make unreachable.
+    pub goals: Vec<CommonGoal>,
+    /// Candidate actions used to reach goals.
+    pub actions: Vec<Action>,
+    /// The maximum number of steps to reach a goal.
+    pub max_depth: usize,
+}
+
+/// A warning that an action makes a common goal unreachable.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Warning {
+    /// The goal that became impossible.
+    pub goal: CommonGoal,
+    /// The action responsible.
+    pub action: Action,
+}
+
+impl Room {
+    /// Returns the common goals that become unreachable by executing an action.
+    ///
+    /// A goal is reachable when the planner finds a plan using the candidate actions
+    /// within `max_depth` steps, searching breadth first so no plan is missed.
+    /// Goals that are unreachable before the action are not reported.
+    /// Returns indices into `goals`.
+    pub fn failed_goals(
+        &self,
+        action: &Action,
+        goals: &[CommonGoal],
+        actions: &[Action],
+        max_depth: usize
+    ) -> Result<Vec<usize>, ActionError> {
+        let mut after = self.clone();
+        after.common_sense = None;
+        after.force_action(action)?;
+        let reachable = |room: &Room, goal: &Goal| {
+            room.plan(goal, actions, max_depth, Search::BreadthFirst).is_ok()
+        };
+        Ok((0..goals.len())
+            .filter(|&i| reachable(self, &goals[i].goal) && !reachable(&after, &goals[i].goal))
+            .collect())
+    }
+}
+
+impl Room {
+    /// Simulates an action and warns about common goals it makes unreachable.
+    ///
+    /// Returns no warnings when there is no common sense settings.
+    pub fn warnings(&self, action: &Action) -> Result<Vec<Warning>, ActionError> {
+        if let Some(ref common_sense) = self.common_sense {
+            let CommonSense {ref goals, ref actions, max_depth} = *common_sense;
+            Ok(self.failed_goals(action, goals, actions, max_depth)?.into_iter()
+                .map(|i| Warning {goal: goals[i].clone(), action: action.clone()})
+                .collect())
+        } else {
+            Ok(vec![])
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use *;
+
+/// A stable handle to an object in a room.
+///
+/// Unlike indices, ids do not change when objects are added or removed,
+/// and unlike descriptions, they do not change when objects get new properties.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ObjectId(pub u64);
+
+impl Room {
+    /// Adds an object to the room and returns its id.
+    pub fn add(&mut self, obj: Object) -> ObjectId {
+        let id = self.new_id();
+        let mut obj = obj;
+        obj.push(Object::Id(id));
+        self.objects.push(obj);
+        id
+    }
+
+    /// Removes an object from the room.
+    ///
+    /// Returns `None` if there is no object with the id.
+    pub fn remove(&mut self, id: ObjectId) -> Option<Object> {
+        let ind = self.index(id)?;
+        self.descriptions.remove(&id);
+        let mut obj = self.objects.remove(ind);
+        obj.remove(&Object::Id(id));
+        match obj {
+            Object::And(ref mut list) if list.len() == 1 => list.pop(),
+            _ => Some(obj),
+        }
+    }
+
+    /// Returns the current index of an object.
+    pub fn index(&self, id: ObjectId) -> Option<usize> {
+        self.find(&Object::Id(id)).ok()
+    }
+
+    /// Returns the id of an object, assigning a new one if it has none.
+    pub fn id(&mut self, ind: usize) -> ObjectId {
+        let found = properties(&self.objects[ind]).iter().find_map(|p| {
+            if let Object::Id(id) = *p {Some(id)} else {None}
+        });
+        match found {
+            Some(id) => id,
+            None => {
+                let id = self.new_id();
+                self.objects[ind].push(Object::Id(id));
+                id
+            }
+        }
+    }
+
+    fn new_id(&mut self) -> ObjectId {
+        let id = ObjectId(self.next_id);
+        self.next_id += 1;
+        id
+    }
+
+    /// Replaces the participants of an action with their ids.
+    ///
+    /// The resolved action keeps working when the descriptions no longer match,
+    /// e.g. "the open door" after the door is closed.
+    /// Participants that can not be identified are kept as descriptions.
+    pub fn resolve(&mut self, action: &Action) -> Result<Action, ActionError> {
+        let Action::Do {
+            ref subject, ref verb, ref object, ref decorate, ref remove,
+            ref remove_placement, ref prevent, ref require, ref distinct,
+        } = *action;
+        let a = self.identify(subject)?;
+        let b = self.identify(object)?;
+        let (a, b) = (self.id(a), self.id(b));
+        let mut bind = |obj: &Object| match self.find(obj) {
+            Ok(ind) => Object::Id(self.id(ind)),
+            Err(_) => obj.clone(),
+        };
+        let mut pairs = |list: &[(Object, Object)]| -> Vec<(Object, Object)> {
+            list.iter().map(|(obj, prop)| (bind(obj), prop.clone())).collect()
+        };
+        let decorate = pairs(decorate);
+        let remove = pairs(remove);
+        let prevent = pairs(prevent);
+        let require = pairs(require);
+        let remove_placement = remove_placement.iter().map(&mut bind).collect();
+        let distinct = distinct.iter().map(&mut bind).collect();
+        Ok(Action::Do {
+            subject: Object::Id(a), verb: verb.clone(), object: Object::Id(b),
+            decorate, remove, remove_placement, prevent, require, distinct,
+        })
+    }
+
+    /// Replaces an id with a description, e.g. for the history of actions.
+    ///
+    /// Descriptions are cached per id and only searched for again
+    /// when they no longer identify the object.
+    pub fn describe_id(&mut self, obj: &Object) -> Object {
+        if let Object::Id(id) = *obj {
+            if let Some(ind) = self.index(id) {
+                if let Some(description) = self.descriptions.get(&id) {
+                    if self.find(description) == Ok(ind) {return description.clone()};
+                }
+                if let Some(description) = self.refer(ind) {
+                    self.descriptions.insert(id, description.clone());
+                    return description;
+                }
+            }
+        }
+        obj.clone()
+    }
+}
+
+/// Returns the properties of an object.
+fn properties(obj: &Object) -> &[Object] {
+    if let Object::And(ref list) = *obj {list} else {std::slice::from_ref(obj)}
+}
@@ -0,0 +1,127 @@
+use *;
+
+/// A feature that verbs can require of their participants.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Feature {
+    /// Can act, e.g. a person.
+    Animate,
+    /// Can be opened and closed, e.g. a door.
+    Openable,
+    /// Can be locked and unlocked, e.g. a door.
+    Lockable,
+    /// Can be picked up and carried, e.g. a key.
+    Carriable,
+    /// Can be climbed, e.g. a ladder.
+    Climbable,
+    /// A feature declared by the user, e.g. "edible".
+    Custom(Arc<String>),
+}
+
+/// Declares the features of objects matching a pattern.
+///
+/// Objects that match no kind in the room are not restricted.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Kind {
+    /// The pattern of objects of this kind.
+    pub pattern: Object,
+    /// The features of objects of this kind.
+    pub features: Vec<Feature>,
+}
+
+impl Kind {
+    /// Creates a new kind.
+    pub fn new(pattern: Object, features: Vec<Feature>) -> Kind {
+        Kind {pattern, features}
+    }
+
+    /// Returns the standard kinds.
+    pub fn standard() -> Vec<Kind> {
+        use Feature::*;
+        use Object::*;
+
+        vec![
+            Kind::new(I, vec![Animate]),
+            Kind::new(You, vec![Animate]),
+            Kind::new(He, vec![Animate]),
+            Kind::new(She, vec![Animate]),
+            Kind::new(of_type("person"), vec![Animate]),
+            Kind::new(of_type("door"), vec![Openable, Lockable]),
+            Kind::new(of_type("window"), vec![Openable]),
+            Kind::new(of_type("key"), vec![Carriable]),
+            Kind::new(KeyTo(Box::new(Any)), vec![Carriable]),
+            Kind::new(of_type("ladder"), vec![Carriable, Climbable]),
+            Kind::new(of_type("bed"), vec![Climbable]),
+            Kind::new(of_type("roof"), vec![]),
+            Kind::new(of_type("ground"), vec![]),
+            Kind::new(of_type("wall"), vec![]),
+        ]
+    }
+}
+
+/// Requires a feature of a participant of a verb.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Restriction {
+    /// The subject of the verb must have the feature.
+    Subject(Verb, Feature),
+    /// The object of the verb must have the feature.
+    Object(Verb, Feature),
+}
+
+impl Restriction {
+    /// Returns the restrictions of built-in verbs.
+    pub fn standard() -> Vec<Restriction> {
+        use Feature::*;
+        use Verb::*;
+
+        let mut res: Vec<Restriction> = Verb::BUILTIN.iter()
+            .filter(|&verb| *verb != StandOn && *verb != LeanToward)
+            .map(|verb| Restriction::Subject(verb.clone(), Animate))
+            .collect();
+        res.extend(vec![
+            Restriction::Object(Kill, Animate),
+            Restriction::Object(Give, Animate),
+            Restriction::Object(Open, Openable),
+            Restriction::Object(Close, Openable),
+            Restriction::Object(Lock, Lockable),
+            Restriction::Object(Unlock, Lockable),
+            Restriction::Object(PickUp, Carriable),
+            Restriction::Object(PutDown, Carriable),
+            Restriction::Object(Carry, Carriable),
+            Restriction::Object(Climb, Climbable),
+        ]);
+        res
+    }
+}
+
+impl Room {
+    /// Returns the features of an object,
+    /// or `None` if the object matches no kind.
+    pub fn features(&self, ind: usize) -> Option<Vec<Feature>> {
+        let mut res: Option<Vec<Feature>> = None;
+        for kind in &self.kinds {
+            if self.taxonomy.matches(&self.objects[ind], &kind.pattern) {
+                let features = res.get_or_insert_with(Vec::new);
+                for feature in &kind.features {
+                    if !features.contains(feature) {features.push(feature.clone())}
+                }
+            }
+        }
+        res
+    }
+
+    /// Returns the restriction violated by the subject `a` or object `b` of a verb,
+    /// together with the index of the participant.
+    pub fn restricted(&self, verb: &Verb, a: usize, b: usize) -> Option<(usize, &Restriction)> {
+        self.restrictions.iter().filter_map(|restriction| {
+            let (ind, feature) = match *restriction {
+                Restriction::Subject(ref v, ref feature) if v == verb => (a, feature),
+                Restriction::Object(ref v, ref feature) if v == verb => (b, feature),
+                _ => return None,
+            };
+            match self.features(ind) {
+                Some(ref features) if !features.contains(feature) => Some((ind, restriction)),
+                _ => None,
+            }
+        }).next()
+    }
+}
@@ -0,0 +1,194 @@
+use *;
+
+/// An action observed in a room, with the room before and after.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Observation {
+    /// The objects in the room before the action.
+    pub before: Vec<Object>,
+    /// The verb.
+    pub verb: Verb,
+    /// The index of the subject.
+    pub subject: usize,
+    /// The index of the object.
+    pub object: usize,
+    /// The objects in the room after the action, or `None` if it failed.
+    pub after: Option<Vec<Object>>,
+}
+
+impl Room {
+    /// Executes an action and records what happened.
+    ///
+    /// Returns `Err` if the subject or object can not be identified.
+    pub fn observe(&mut self, action: &Action) -> Result<Observation, ActionError> {
+        let Action::Do {ref subject, ref verb, ref object, ..} = *action;
+        let subject = self.identify(subject)?;
+        let object = self.identify(object)?;
+        let before = self.objects.clone();
+        let after = self.action(action).ok().map(|_| self.objects.clone());
+        Ok(Observation {before, verb: verb.clone(), subject, object, after})
+    }
+}
+
+/// The names of the parameters of learned schemas.
+const ROLES: [&str; 2] = ["subject", "object"];
+
+/// Returns the properties of a participant that can be learned,
+/// with the other participant replaced by a variable.
+fn properties(objects: &[Object], roles: [usize; 2]) -> [Vec<Object>; 2] {
+    use Object::*;
+
+    let noun = |obj: &Object| matches!(obj, I | You | He | She | It | That | Called(_) |
+                                      OfType(_) | KeyTo(_) | And(_));
+    let lift = |prop: &Object| -> Object {
+        if noun(prop) {return prop.clone()};
+        prop.substitute(&|part| {
+            if !noun(part) {return None};
+            ROLES.iter().zip(roles.iter())
+                .find(|&(_, &ind)| objects[ind].matches(part))
+                .map(|(name, _)| var(name))
+        })
+    };
+    let props = |ind: usize| -> Vec<Object> {
+        let list: &[Object] = match objects[ind] {
+            And(ref list) => list,
+            ref obj => std::slice::from_ref(obj),
+        };
+        list.iter()
+            .filter(|p| !matches!(p, WasBy(_, _) | DidTo(_, _) | Id(_)))
+            .map(lift)
+            .collect()
+    };
+    [props(roles[0]), props(roles[1])]
+}
+
+impl ActionSchema {
+    /// Learns an action with parameters `subject` and `object` from observations of a verb.
+    ///
+    /// Properties that every success adds become `decorate`,
+    /// and properties that every success removes become `remove`.
+    /// Failures are explained by a minimal set of `require` and `prevent` constraints,
+    /// where no constraint can be left out, although a smaller set might exist.
+    pub fn learn(name: &str, verb: Verb, observations: &[Observation]) -> ActionSchema {
+        let roles = |o: &Observation| [o.subject, o.object];
+        let successes: Vec<&Observation> = observations.iter()
+            .filter(|o| o.verb == verb && o.after.is_some()).collect();
+        let failures: Vec<&Observation> = observations.iter()
+            .filter(|o| o.verb == verb && o.after.is_none()).collect();
+        let before: Vec<[Vec<Object>; 2]> = successes.iter()
+            .map(|o| properties(&o.before, roles(o))).collect();
+        let after: Vec<[Vec<Object>; 2]> = successes.iter()
+            .map(|o| properties(o.after.as_ref().unwrap(), roles(o))).collect();
+        let failed: Vec<[Vec<Object>; 2]> = failures.iter()
+            .map(|o| properties(&o.before, roles(o))).collect();
+
+        let distinct = !successes.iter().any(|o| o.subject == o.object);
+        let mut decorate = vec![];
+        let mut remove = vec![];
+        let mut remove_placement = vec![];
+        let mut require: Vec<(usize, Object)> = vec![];
+        let mut prevent: Vec<(usize, Object)> = vec![];
+        for r in 0..2 {
+            if successes.is_empty() {break};
+            let mut seen: Vec<&Object> = vec![];
+            for props in before.iter().chain(after.iter()).chain(failed.iter()) {
+                for p in &props[r] {
+                    if !seen.contains(&p) {seen.push(p)};
+                }
+            }
+            let is_placement = |p: &Object| matches!(p, Object::Placement(_));
+            for &p in &seen {
+                let all_after = after.iter().all(|a| a[r].contains(p));
+                let none_after = after.iter().all(|a| !a[r].contains(p));
+                let some_before = before.iter().any(|b| b[r].contains(p));
+                let some_not_before = before.iter().any(|b| !b[r].contains(p));
+                if all_after && some_not_before {decorate.push((r, p.clone()))};
+                if none_after && some_before && !is_placement(p) {remove.push((r, p.clone()))};
+                if before.iter().all(|b| b[r].contains(p)) {require.push((r, p.clone()))};
+                if !some_before && failed.iter().any(|f| f[r].contains(p)) {
+                    prevent.push((r, p.clone()));
+                }
+            }
+            // Moving from one place to another removes the old placement.
+            let moved = (0..successes.len()).all(|i| {
+                before[i][r].iter().any(|p| is_placement(p) && !after[i][r].contains(p))
+            });
+            if moved {remove_placement.push(var(ROLES[r]))};
+        }
+
+        // Pick constraints that explain every failure that can be explained, without redundant ones.
+        let explainable: Vec<usize> = (0..failures.len())
+            .filter(|&i| !(distinct && failures[i].subject == failures[i].object))
+            .collect();
+        let candidates: Vec<(bool, &(usize, Object))> = require.iter().map(|c| (true, c))
+            .chain(prevent.iter().map(|c| (false, c))).collect();
+        let explained: Vec<Vec<usize>> = candidates.iter()
+            .map(|&(require_it, &(r, ref p))| {
+                explainable.iter().cloned().filter(|&i| failed[i][r].contains(p) != require_it).collect()
+            })
+            .collect();
+        let mut chosen_require = vec![];
+        let mut chosen_prevent = vec![];
+        for c in min_cover(&explained) {
+            let (require_it, constraint) = candidates[c];
+            let chosen = if require_it {&mut chosen_require} else {&mut chosen_prevent};
+            chosen.push(constraint.clone());
+        }
+
+        let pairs = |list: Vec<(usize, Object)>| -> Vec<(Object, Object)> {
+            list.into_iter().map(|(r, p)| (var(ROLES[r]), p)).collect()
+        };
+        ActionSchema {
+            name: Arc::new(name.into()),
+            params: ROLES.iter().map(|r| Arc::new(r.to_string())).collect(),
+            action: Action::Do {
+                subject: var(ROLES[0]), verb, object: var(ROLES[1]),
+                decorate: pairs(decorate),
+                remove: pairs(remove),
+                remove_placement,
+                prevent: pairs(chosen_prevent),
+                require: pairs(chosen_require),
+                distinct: if distinct {vec![var(ROLES[0]), var(ROLES[1])]} else {vec![]},
+            },
+        }
+    }
+}
+
+/// Returns the indices of sets that together contain every element of the sets,
+/// such that no set can be left out.
+///
+/// Sets that are the only ones containing some element are picked first,
+/// then the set containing the most missing elements until all are covered.
+/// Finally, sets made redundant by later picks are dropped.
+fn min_cover(sets: &[Vec<usize>]) -> Vec<usize> {
+    let mut all: Vec<usize> = sets.iter().flatten().cloned().collect();
+    all.sort();
+    all.dedup();
+    let covered = |chosen: &[usize], i: &usize| chosen.iter().any(|&c| sets[c].contains(i));
+
+    let mut chosen: Vec<usize> = vec![];
+    for i in &all {
+        let mut covering = (0..sets.len()).filter(|&c| sets[c].contains(i));
+        if let (Some(c), None) = (covering.next(), covering.next()) {
+            if !chosen.contains(&c) {chosen.push(c)};
+        }
+    }
+    loop {
+        let missing: Vec<usize> = all.iter().filter(|i| !covered(&chosen, i)).cloned().collect();
+        if missing.is_empty() {break};
+        // Prefer the first set when several contain as many missing elements.
+        let best = (0..sets.len())
+            .max_by_key(|&c| {
+                (missing.iter().filter(|i| sets[c].contains(i)).count(), sets.len() - c)
+            })
+            .unwrap();
+        chosen.push(best);
+    }
+    // Drop the latest picks first, since the first ones are needed.
+    for k in (0..chosen.len()).rev() {
+        let mut rest = chosen.clone();
+        rest.remove(k);
+        if all.iter().all(|i| covered(&rest, i)) {chosen = rest};
+    }
+    chosen.sort();
+    chosen
+}
@@ -20,25 +20,56 @@
 //! The motivation is to derive which kind of constraints that occur naturally,
 //! such that these constraints can later be translated into machine learning problems.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
-pub use verb::Verb;
+pub use verb::{Verb, VerbInfo, VerbRegistry};
+pub use adjective::{Adjective, Adjectives};
+pub use planner::{Goal, NoPlan, Search};
+pub use goals::{CommonGoal, CommonSense, Warning};
+pub use parser::{parse_action, parse_object, parse_question, parse_statement, ParseError};
+pub use scenario::{Failure, Scenario, ScenarioError, Step};
+pub use schema::{ActionSchema, SchemaError};
+pub use rules::Rule;
+pub use consistency::Inconsistency;
+pub use kinds::{Feature, Kind, Restriction};
+pub use dialogue::Clarification;
+pub use discourse::Discourse;
+pub use ids::ObjectId;
+pub use query::Bindings;
+pub use qa::{Answer, Question};
+pub use babi::{BabiError, BabiLine, BabiResult, BabiTask};
+pub use dataset::{Generator, Record};
+pub use learn::Observation;
+pub use ablation::{ablate, ablation_table, Ablation, AblationReport, Constraint};
+pub use taxonomy::{Taxonomy, TaxonomyError};
 
 mod verb;
+mod adjective;
 mod actions;
 mod helpers;
+mod planner;
+mod goals;
+mod parser;
+mod english;
+mod scenario;
+mod schema;
+mod rules;
+mod consistency;
+mod kinds;
+mod taxonomy;
+mod reference;
+mod dialogue;
+mod discourse;
+mod ids;
+mod query;
+mod qa;
+mod babi;
+mod dataset;
+mod learn;
+mod ablation;
 
-#[derive(Clone, Debug, PartialEq)]
-pub enum Adjective {
-    Dead,
-    Murderer,
-    Open,
-    Closed,
-    Locked,
-    Unlocked,
-}
-
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Placement {
     On(Object),
     LeanToward(Object),
@@ -48,13 +79,17 @@ pub enum Placement {
 
 impl Placement {
     pub fn matches(&self, other: &Placement) -> bool {
+        self.matches_by(other, &|a, b| a == b)
+    }
+
+    fn matches_by(&self, other: &Placement, subtype: &dyn Fn(&str, &str) -> bool) -> bool {
         use Placement::*;
 
         match (self, other) {
-            (&On(ref a), &On(ref b)) => a.matches(b),
-            (&LeanToward(ref a), &LeanToward(ref b)) => a.matches(b),
-            (&In(ref a), &In(ref b)) => a.matches(b),
-            (&OutOf(ref a), &OutOf(ref b)) => a.matches(b),
+            (On(a), On(b)) => a.matches_by(b, subtype),
+            (LeanToward(a), LeanToward(b)) => a.matches_by(b, subtype),
+            (In(a), In(b)) => a.matches_by(b, subtype),
+            (OutOf(a), OutOf(b)) => a.matches_by(b, subtype),
 
             (&On(_), _) | (_, &On(_)) => false,
             (&LeanToward(_), _) | (_, &LeanToward(_)) => false,
@@ -62,6 +97,18 @@ impl Placement {
         }
     }
 
+    /// Replaces parts of the object placed relative to.
+    pub fn substitute(&self, f: &dyn Fn(&Object) -> Option<Object>) -> Placement {
+        use Placement::*;
+
+        match *self {
+            On(ref obj) => On(obj.substitute(f)),
+            LeanToward(ref obj) => LeanToward(obj.substitute(f)),
+            In(ref obj) => In(obj.substitute(f)),
+            OutOf(ref obj) => OutOf(obj.substitute(f)),
+        }
+    }
+
     pub fn obj_ref(&self) -> &Object {
         use Placement::*;
 
@@ -74,17 +121,28 @@ impl Placement {
     }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Role {
     OpponentOf(Object),
 }
 
 impl Role {
     pub fn matches(&self, other: &Role) -> bool {
+        self.matches_by(other, &|a, b| a == b)
+    }
+
+    fn matches_by(&self, other: &Role, subtype: &dyn Fn(&str, &str) -> bool) -> bool {
         use Role::*;
 
         match (self, other) {
-            (&OpponentOf(ref a), &OpponentOf(ref b)) => a.matches(b),
+            (OpponentOf(a), OpponentOf(b)) => a.matches_by(b, subtype),
+        }
+    }
+
+    /// Replaces parts of the object in the role.
+    pub fn substitute(&self, f: &dyn Fn(&Object) -> Option<Object>) -> Role {
+        match *self {
+            Role::OpponentOf(ref obj) => Role::OpponentOf(obj.substitute(f)),
         }
     }
 
@@ -115,7 +173,7 @@ impl From<Role> for Object {
     }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Object {
     I,
     You,
@@ -134,20 +192,34 @@ pub enum Object {
     WasBy(Verb, Box<Object>),
     DidTo(Verb, Box<Object>),
     KeyTo(Box<Object>),
+    /// A variable in a template, substituted before use.
+    Var(Arc<String>),
+    /// Matches any object when used in a pattern.
+    Any,
+    /// The stable identity of an object, assigned by the room.
+    Id(ObjectId),
 }
 
 impl Object {
     /// Returns `true` if the object matches another.
+    ///
+    /// Types match exactly. Use `Taxonomy::matches` to match sub-types.
     pub fn matches(&self, other: &Object) -> bool {
+        self.matches_by(other, &|a, b| a == b)
+    }
+
+    /// Matches types when `subtype` returns `true` for the type and the type in the pattern.
+    fn matches_by(&self, other: &Object, subtype: &dyn Fn(&str, &str) -> bool) -> bool {
         use Object::*;
 
         match (self, other) {
-            (_, &And(ref objs)) => {
+            (_, &Any) => true,
+            (_, And(objs)) => {
                 // Match everyone since there are more than one criteria.
-                objs.iter().all(|obj| self.matches(obj))
+                objs.iter().all(|obj| self.matches_by(obj, subtype))
             }
-            (&And(ref objs), _) => {
-                objs.iter().any(|obj| obj.matches(other))
+            (And(objs), _) => {
+                objs.iter().any(|obj| obj.matches_by(other, subtype))
             }
             (&I, &I) => true,
             (&You, &You) => true,
@@ -155,16 +227,18 @@ impl Object {
             (&She, &She) => true,
             (&It, &It) => true,
             (&That, &That) => true,
-            (&Placement(ref a), &Placement(ref b)) => a.matches(b),
-            (&Role(ref a), &Role(ref b)) => a.matches(b),
-            (&Has(ref a), &Has(ref b)) => a.matches(b),
-            (&HasNot(ref a), &HasNot(ref b)) => a.matches(b),
-            (&Called(ref a), &Called(ref b)) => a == b,
-            (&OfType(ref a), &OfType(ref b)) => a == b,
-            (&Adj(ref a), &Adj(ref b)) => a == b,
-            (&WasBy(va, ref a), &WasBy(vb, ref b)) => va == vb && a.matches(b),
-            (&DidTo(va, ref a), &DidTo(vb, ref b)) => va == vb && a.matches(b),
-            (&KeyTo(ref a), &KeyTo(ref b)) => a.matches(b),
+            (Placement(a), Placement(b)) => a.matches_by(b, subtype),
+            (Role(a), Role(b)) => a.matches_by(b, subtype),
+            (Has(a), Has(b)) => a.matches_by(b, subtype),
+            (HasNot(a), HasNot(b)) => a.matches_by(b, subtype),
+            (Called(a), Called(b)) => a == b,
+            (OfType(a), OfType(b)) => subtype(a, b),
+            (Adj(a), Adj(b)) => a == b,
+            (WasBy(va, a), WasBy(vb, b)) => va == vb && a.matches_by(b, subtype),
+            (DidTo(va, a), DidTo(vb, b)) => va == vb && a.matches_by(b, subtype),
+            (KeyTo(a), KeyTo(b)) => a.matches_by(b, subtype),
+            (Var(a), Var(b)) => a == b,
+            (Id(a), Id(b)) => a == b,
 
             (&I, _) | (_, &I) => false,
             (&You, _) | (_, &You) => false,
@@ -181,6 +255,9 @@ impl Object {
             (&Adj(_), _) | (_, &Adj(_)) => false,
             (&WasBy(_, _), _) | (_, &WasBy(_, _)) => false,
             (&DidTo(_, _), _) | (_, &DidTo(_, _)) => false,
+            (&KeyTo(_), _) | (_, &KeyTo(_)) => false,
+            (&Id(_), _) | (_, &Id(_)) => false,
+            (&Any, _) => false,
         }
     }
 
@@ -203,8 +280,7 @@ impl Object {
 
         if let And(ref mut list) = *self {
             for i in (0..list.len()).rev() {
-                let found = if let Placement(_) = list[i] {true} else {false};
-                if found {list.remove(i);}
+                if let Placement(_) = list[i] {list.remove(i);}
             }
         }
     }
@@ -221,9 +297,38 @@ impl Object {
             }
         }
     }
+
+    /// Replaces parts of the object, starting from the outermost.
+    ///
+    /// The function returns `Some` for parts that should be replaced.
+    pub fn substitute(&self, f: &dyn Fn(&Object) -> Option<Object>) -> Object {
+        use Object::*;
+
+        if let Some(obj) = f(self) {return obj};
+        let sub = |obj: &Object| Box::new(obj.substitute(f));
+        match *self {
+            And(ref list) => And(list.iter().map(|obj| obj.substitute(f)).collect()),
+            Placement(ref place) => Placement(Box::new(place.substitute(f))),
+            Role(ref role) => Role(Box::new(role.substitute(f))),
+            Has(ref obj) => Has(sub(obj)),
+            HasNot(ref obj) => HasNot(sub(obj)),
+            WasBy(ref verb, ref obj) => WasBy(verb.clone(), sub(obj)),
+            DidTo(ref verb, ref obj) => DidTo(verb.clone(), sub(obj)),
+            KeyTo(ref obj) => KeyTo(sub(obj)),
+            I | You | He | She | It | That | Called(_) | OfType(_) | Adj(_) | Var(_) | Any | Id(_) => {
+                self.clone()
+            }
+        }
+    }
+
+    /// Replaces every occurrence of an object with another.
+    pub fn replace(&self, from: &Object, to: &Object) -> Object {
+        self.substitute(&|obj| if obj == from {Some(to.clone())} else {None})
+    }
 }
 
 /// Stores an action.
+#[derive(Clone, Debug, PartialEq)]
 pub enum Action {
     /// Do something.
     Do {
@@ -248,13 +353,112 @@ pub enum Action {
     },
 }
 
+impl Action {
+    /// Replaces parts of all objects in the action.
+    pub fn substitute(&self, f: &dyn Fn(&Object) -> Option<Object>) -> Action {
+        let Action::Do {
+            ref subject, ref verb, ref object, ref decorate, ref remove,
+            ref remove_placement, ref prevent, ref require, ref distinct,
+        } = *self;
+        let pairs = |list: &[(Object, Object)]| list.iter()
+            .map(|(a, b)| (a.substitute(f), b.substitute(f)))
+            .collect();
+        let list = |list: &[Object]| list.iter().map(|a| a.substitute(f)).collect();
+        Action::Do {
+            subject: subject.substitute(f),
+            verb: verb.clone(),
+            object: object.substitute(f),
+            decorate: pairs(decorate),
+            remove: pairs(remove),
+            remove_placement: list(remove_placement),
+            prevent: pairs(prevent),
+            require: pairs(require),
+            distinct: list(distinct),
+        }
+    }
+
+    /// Replaces every occurrence of an object with another.
+    pub fn replace(&self, from: &Object, to: &Object) -> Action {
+        self.substitute(&|obj| if obj == from {Some(to.clone())} else {None})
+    }
+}
+
+/// Describes why an action failed.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ActionError {
+    /// There is no object in the room matching the description.
+    NotFound(Object),
+    /// There are more than one object in the room matching the description.
+    ///
+    /// Stores the indices of the candidates.
+    Ambiguous(Object, Vec<usize>),
+    /// Two objects in the distinct group refer to the same object.
+    ///
+    /// Stores the positions of the colliding objects in the group.
+    Distinct(usize, usize),
+    /// A required property is missing.
+    Require(Object, Object),
+    /// A property that prevents the action is present.
+    Prevent(Object, Object),
+    /// The subject can not act because it matches a pattern in the rules.
+    CannotAct(Object, Object),
+    /// The object can not respond because it matches a pattern in the rules.
+    Unresponsive(Object, Object),
+    /// A participant lacks a feature required by the verb.
+    Restricted(Object, Restriction),
+    /// The action would make the room inconsistent.
+    Inconsistent(Vec<Inconsistency>),
+    /// The action makes common goals unreachable.
+    ///
+    /// Use `Room::force_action` to execute the action anyway.
+    CommonSense(Vec<Warning>),
+}
+
+#[derive(Clone)]
 pub struct Room {
-    pub objects: Vec<Object>
+    pub objects: Vec<Object>,
+    /// Checks actions against common goals when set.
+    pub common_sense: Option<CommonSense>,
+    /// The background theory, applied after every action.
+    pub rules: Vec<Rule>,
+    /// Pairs of patterns that no object may match at the same time.
+    pub invariants: Vec<(Object, Object)>,
+    /// Rejects actions that make the room inconsistent when `true`.
+    pub strict: bool,
+    /// Declares the features of objects.
+    pub kinds: Vec<Kind>,
+    /// Declares the features that verbs require of their participants.
+    pub restrictions: Vec<Restriction>,
+    /// The custom verbs that sentences about the room can use.
+    pub verbs: VerbRegistry,
+    /// Custom adjectives and groups of adjectives that exclude each other.
+    pub adjectives: Adjectives,
+    /// Sub-type relations used when finding objects.
+    pub taxonomy: Taxonomy,
+    /// The next id to assign to an object.
+    next_id: u64,
+    /// Cached descriptions of objects with ids, used in the history of actions.
+    descriptions: HashMap<ObjectId, Object>,
 }
 
 impl Room {
     /// Creates a new room with objects.
-    pub fn new(objects: Vec<Object>) -> Room {Room {objects}}
+    pub fn new(objects: Vec<Object>) -> Room {
+        Room {
+            objects,
+            common_sense: None,
+            rules: Rule::standard(),
+            invariants: vec![],
+            strict: false,
+            kinds: Kind::standard(),
+            restrictions: Restriction::standard(),
+            verbs: VerbRegistry::new(),
+            adjectives: Adjectives::standard(),
+            taxonomy: Taxonomy::new(),
+            next_id: 0,
+            descriptions: HashMap::new(),
+        }
+    }
 
     /// Finds object in room.
     ///
@@ -263,7 +467,7 @@ impl Room {
     pub fn find(&self, obj: &Object) -> Result<usize, Vec<usize>> {
         let mut res = vec![];
         for i in 0..self.objects.len() {
-            if self.objects[i].matches(obj) {
+            if self.taxonomy.matches(&self.objects[i], obj) {
                 res.push(i);
             }
         }
@@ -271,12 +475,60 @@ impl Room {
         else {Err(res)}
     }
 
+    /// Identifies a single object in the room.
+    pub fn identify(&self, obj: &Object) -> Result<usize, ActionError> {
+        match self.find(obj) {
+            Ok(ind) => Ok(ind),
+            Err(ref candidates) if candidates.is_empty() => Err(ActionError::NotFound(obj.clone())),
+            Err(candidates) => Err(ActionError::Ambiguous(obj.clone(), candidates)),
+        }
+    }
+
+    /// Returns `true` if an object in the room has some property.
+    ///
+    /// Returns `false` if the object can not be identified.
+    pub fn holds(&self, obj: &Object, property: &Object) -> bool {
+        if let Ok(ind) = self.find(obj) {
+            self.taxonomy.matches(&self.objects[ind], property)
+        } else {
+            false
+        }
+    }
+
     /// Executate an action in the room.
-    pub fn action(&mut self, action: &Action) -> Result<(), ()> {
+    ///
+    /// When common sense is enabled, the action is simulated first
+    /// and fails if it makes some common goal unreachable.
+    pub fn action(&mut self, action: &Action) -> Result<(), ActionError> {
+        let warnings = self.warnings(action)?;
+        if !warnings.is_empty() {return Err(ActionError::CommonSense(warnings))};
+        self.force_action(action)
+    }
+
+    /// Executes an action in the room without checking common sense.
+    ///
+    /// In strict mode, the action fails if the room becomes inconsistent.
+    /// Inconsistencies that were there before the action are ignored.
+    pub fn force_action(&mut self, action: &Action) -> Result<(), ActionError> {
+        if !self.strict {return self.execute(action)};
+        let before = self.objects.clone();
+        let existing = self.check_consistency();
+        self.execute(action)?;
+        let inconsistencies: Vec<Inconsistency> = self.check_consistency().into_iter()
+            .filter(|inconsistency| !existing.contains(inconsistency))
+            .collect();
+        if inconsistencies.is_empty() {Ok(())}
+        else {
+            self.objects = before;
+            Err(ActionError::Inconsistent(inconsistencies))
+        }
+    }
+
+    fn execute(&mut self, action: &Action) -> Result<(), ActionError> {
         match *action {
             Action::Do {
                 ref subject,
-                verb,
+                ref verb,
                 ref object,
                 ref decorate,
                 ref remove,
@@ -285,54 +537,61 @@ impl Room {
                 ref prevent,
                 ref distinct,
             } => {
-                match (self.find(subject), self.find(object)) {
-                    (Ok(a), Ok(b)) => {
-                        // Check that objects in distinctive group are distinctive.
-                        let mut ids = vec![];
-                        for obj in distinct {
-                            if let Ok(a) = self.find(obj) {
-                                for i in 0..ids.len() {
-                                    if ids[i] == a {return Err(())};
-                                }
-                                ids.push(a);
-                            }
-                        }
+                let a = self.identify(subject)?;
+                let b = self.identify(object)?;
+                if let Some(pattern) = self.cannot_act(a, verb) {
+                    return Err(ActionError::CannotAct(subject.clone(), pattern.clone()));
+                }
+                if let Some(pattern) = self.unresponsive(b, verb) {
+                    return Err(ActionError::Unresponsive(object.clone(), pattern.clone()));
+                }
 
-                        if !require.iter().all(|(ref obj, ref adj)| {
-                            if let Ok(ind) = self.find(obj) {
-                                self.objects[ind].matches(&adj.clone().into())
-                            } else {
-                                false
-                            }
-                        }) {return Err(())};
-                        if prevent.iter().any(|&(ref obj, ref adj)| {
-                            if let Ok(ind) = self.find(obj) {
-                                self.objects[ind].matches(&adj.clone().into())
-                            } else {
-                                false
-                            }
-                        }) {return Err(())};
-                        for &(ref obj, ref removal) in remove {
-                            if let Ok(ind) = self.find(obj) {
-                                self.objects[ind].remove(&removal.clone().into());
-                            }
-                        }
-                        for obj in remove_placement {
-                            if let Ok(ind) = self.find(obj) {
-                                self.objects[ind].remove_placement();
-                            }
+                // Check that objects in distinctive group are distinctive.
+                let mut ids: Vec<(usize, usize)> = vec![];
+                for (j, obj) in distinct.iter().enumerate() {
+                    if let Ok(ind) = self.find(obj) {
+                        if let Some(&(i, _)) = ids.iter().find(|&&(_, id)| id == ind) {
+                            return Err(ActionError::Distinct(i, j));
                         }
-                        for &(ref obj, ref decor) in decorate {
-                            if let Ok(ind) = self.find(obj) {
-                                self.objects[ind].push(decor.clone());
-                            }
-                        }
-                        self.objects[a].push(Object::DidTo(verb, Box::new(object.clone())));
-                        self.objects[b].push(Object::WasBy(verb, Box::new(subject.clone())));
-                        Ok(())
+                        ids.push((j, ind));
+                    }
+                }
+
+                if let Some((ind, restriction)) = self.restricted(verb, a, b) {
+                    let obj = if ind == a {subject} else {object};
+                    return Err(ActionError::Restricted(obj.clone(), restriction.clone()));
+                }
+
+                for (obj, adj) in require {
+                    if !self.holds(obj, adj) {
+                        return Err(ActionError::Require(obj.clone(), adj.clone()));
+                    }
+                }
+                for (obj, adj) in prevent {
+                    if self.holds(obj, adj) {
+                        return Err(ActionError::Prevent(obj.clone(), adj.clone()));
                     }
-                    _ => Err(())
                 }
+                for (obj, removal) in remove {
+                    if let Ok(ind) = self.find(obj) {
+                        self.objects[ind].remove(removal);
+                    }
+                }
+                for obj in remove_placement {
+                    if let Ok(ind) = self.find(obj) {
+                        self.objects[ind].remove_placement();
+                    }
+                }
+                for (obj, decor) in decorate {
+                    if let Ok(ind) = self.find(obj) {
+                        self.adjectives.decorate(&mut self.objects[ind], decor.clone());
+                    }
+                }
+                let (subject, object) = (self.describe_id(subject), self.describe_id(object));
+                self.objects[a].push(Object::DidTo(verb.clone(), Box::new(object)));
+                self.objects[b].push(Object::WasBy(verb.clone(), Box::new(subject)));
+                self.apply_rules();
+                Ok(())
             }
         }
     }
@@ -347,6 +606,8 @@ pub fn has_not(obj: Object) -> Object {Object::HasNot(Box::new(obj))}
 pub fn called(name: &str) -> Object {Object::Called(Arc::new(name.into()))}
 pub fn of_type(name: &str) -> Object {Object::OfType(Arc::new(name.into()))}
 pub fn key_to(obj: Object) -> Object {Object::KeyTo(Box::new(obj))}
+pub fn var(name: &str) -> Object {Object::Var(Arc::new(name.into()))}
+pub fn adj(name: &str) -> Adjective {Adjective::Custom(Arc::new(name.into()))}
 
 pub fn killed_by(obj: Object) -> Object {Object::WasBy(Verb::Kill, Box::new(obj))}
 pub fn killed(obj: Object) -> Object {Object::DidTo(Verb::Kill, Box::new(obj))}
@@ -581,6 +842,904 @@ mod tests {
         assert!(room.objects[i].is_in(of_type("bed")));
     }
 
+    #[test]
+    fn test_action_error() {
+        let mut room = Room::new(vec![I, of_type("door"), of_type("door")]);
+        assert_eq!(room.action(&I.opens(of_type("door"))),
+                   Err(ActionError::Ambiguous(of_type("door"), vec![1, 2])));
+        assert_eq!(room.action(&I.opens(of_type("window"))),
+                   Err(ActionError::NotFound(of_type("window"))));
+
+        let mut room = Room::new(vec![I, of_type("door")]);
+        assert_eq!(room.action(&I.opens(I)), Err(ActionError::Distinct(0, 1)));
+        room.action(&I.locks(of_type("door"))).unwrap();
+        assert_eq!(room.action(&I.opens(of_type("door"))),
+                   Err(ActionError::Prevent(of_type("door"), Locked.into())));
+        assert_eq!(room.action(&I.unlocks(of_type("door"))),
+                   Err(ActionError::Require(I, has(key_to(of_type("door"))))));
+    }
+
+    #[test]
+    fn test_plan() {
+        let door = And(vec![of_type("door"), Locked.into(), Closed.into()]);
+        let room = Room::new(vec![I, door, key_to(of_type("door"))]);
+        let actions = vec![
+            I.opens(of_type("door")),
+            I.walks_through(of_type("door")),
+            I.picks_up(key_to(of_type("door"))),
+            I.unlocks(of_type("door")),
+        ];
+        let goal = Goal::new(vec![
+            (I, DidTo(Verb::WalkThrough, Box::new(of_type("door")))),
+        ]);
+        let plan = vec![2, 3, 0, 1];
+        assert_eq!(room.plan(&goal, &actions, 10, Search::BreadthFirst), Ok(plan.clone()));
+        assert_eq!(room.plan(&goal, &actions, 10, Search::AStar), Ok(plan));
+        assert!(matches!(room.plan(&goal, &actions, 2, Search::BreadthFirst),
+                         Err(NoPlan::Bound {..})));
+
+        // There is no key, so the door can not be unlocked.
+        let room = Room::new(vec![I, And(vec![of_type("door"), Locked.into(), Closed.into()])]);
+        assert_eq!(room.plan(&goal, &actions, 10, Search::BreadthFirst),
+                   Err(NoPlan::Impossible {states: 1}));
+    }
+
+    #[test]
+    fn test_failed_goals() {
+        let room = Room::new(vec![I, She, of_type("door")]);
+        let goals = vec![
+            CommonGoal::leave_through(I, of_type("door")),
+            CommonGoal::stay_alive(She),
+        ];
+        let actions = vec![
+            I.opens(of_type("door")),
+            I.walks_through(of_type("door")),
+            I.unlocks(of_type("door")),
+        ];
+        assert_eq!(room.failed_goals(&I.talk_to(She), &goals, &actions, 5), Ok(vec![]));
+        assert_eq!(room.failed_goals(&I.kills(She), &goals, &actions, 5), Ok(vec![1]));
+        // There is no key to unlock the door.
+        assert_eq!(room.failed_goals(&I.locks(of_type("door")), &goals, &actions, 5), Ok(vec![0]));
+        assert!(room.failed_goals(&I.kills(He), &goals, &actions, 5).is_err());
+    }
+
+    #[test]
+    fn test_common_sense() {
+        let mut climb_down = I.climbs_to(of_type("ladder"), on(of_type("ground")));
+        {
+            let Action::Do {ref mut require, ..} = climb_down;
+            require.push((of_type("ladder"), lean_toward(of_type("roof")).into()));
+        }
+        let get_down = CommonGoal::get_down(I, on(of_type("roof")), on(of_type("ground")));
+        let mut room = Room::new(vec![
+            And(vec![I, on(of_type("roof")).into()]),
+            And(vec![of_type("ladder"), lean_toward(of_type("roof")).into()]),
+            of_type("roof"),
+            of_type("ground"),
+            She,
+        ]);
+        room.common_sense = Some(CommonSense {
+            goals: vec![get_down.clone(), CommonGoal::stay_alive(She)],
+            actions: vec![climb_down, I.puts_down(of_type("ladder"))],
+            max_depth: 3,
+        });
+        room.action(&I.talk_to(She)).unwrap();
+        let pick_up = I.picks_up(of_type("ladder"));
+        assert_eq!(room.action(&pick_up), Err(ActionError::CommonSense(vec![
+            Warning {goal: get_down, action: pick_up.clone()}
+        ])));
+        assert!(room.objects[1].is_leaning_toward(of_type("roof")));
+        match room.action(&I.kills(She)) {
+            Err(ActionError::CommonSense(warnings)) => {
+                assert_eq!(&**warnings[0].goal.name, "stay alive");
+            }
+            x => panic!("{:?}", x),
+        }
+        room.force_action(&pick_up).unwrap();
+        assert!(!room.objects[1].is_leaning_toward(of_type("roof")));
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse_action("I climb the ladder onto the roof."),
+                   Ok(I.climbs_to(of_type("ladder"), on(of_type("roof")))));
+        assert_eq!(parse_action("Peter gives the key to her"),
+                   Ok(called("Peter").gives_item(She, of_type("key"))));
+        assert_eq!(parse_action("Peter gives her the key to the door"),
+                   Ok(called("Peter").gives_item(She, key_to(of_type("door")))));
+        assert_eq!(parse_action("she kills John"), Ok(She.kills(called("John"))));
+        assert_eq!(parse_action("I climb out of the bed"),
+                   Ok(I.climbs_out_of(of_type("bed"))));
+        assert_eq!(parse_action("He picked up the key to the door"),
+                   Ok(He.picks_up(key_to(of_type("door")))));
+        assert_eq!(parse_object("the locked door"),
+                   Ok(And(vec![of_type("door"), Locked.into()])));
+
+        assert_eq!(parse_action("I eat the apple"),
+                   Err(ParseError::UnknownVerb("eat".into())));
+        assert_eq!(parse_action("I open door"),
+                   Err(ParseError::ExpectedNounPhrase("door".into())));
+        assert_eq!(parse_action("I pick the key"),
+                   Err(ParseError::Expected("up", "the".into())));
+        assert_eq!(parse_action("I kill"), Err(ParseError::UnexpectedEnd));
+        assert_eq!(parse_action("I open the door now"),
+                   Err(ParseError::Trailing("now".into())));
+    }
+
+    #[test]
+    fn test_english() {
+        let mut room = Room::new(vec![called("Peter"), called("John"), called("Sheila")]);
+        room.action(&called("Peter").kills(called("John"))).unwrap();
+        room.action(&called("Sheila").kills(called("Peter"))).unwrap();
+        assert_eq!(format!("{}", room.objects[0]),
+                   "Peter, who killed John, is a murderer, is dead and was killed by Sheila");
+
+        let mut room = Room::new(vec![I, of_type("door"), key_to(of_type("door"))]);
+        room.action(&I.locks(of_type("door"))).unwrap();
+        room.action(&I.picks_up(key_to(of_type("door")))).unwrap();
+        assert_eq!(format!("{}", room.objects[1]), "the locked, closed door that I locked");
+        assert_eq!(room.describe(), "I locked the door, have the key to the door \
+                                     and picked up the key to the door. \
+                                     The door is locked, was locked by me and is closed. \
+                                     The key to the door was picked up by me.");
+
+        assert_eq!(format!("{}", called("Peter").gives_item(She, It)), "Peter gave it to her.");
+        assert_eq!(format!("{}", I.climbs_to(of_type("ladder"), on(of_type("roof")))),
+                   "I climbed the ladder onto the roof.");
+        assert_eq!(format!("{}", I.climbs_into(of_type("bed"))), "I climbed into the bed.");
+        assert_eq!(format!("{}", He.picks_up(It)), "He picked it up.");
+        assert_eq!(format!("{}", She.puts_down(of_type("key"))), "She put down the key.");
+        assert_eq!(format!("{}", ActionError::NotFound(of_type("window"))),
+                   "There is no window.");
+        assert_eq!(format!("{}", ActionError::Require(I, has(It))),
+                   "It is required that I have it.");
+    }
+
+    #[test]
+    fn test_scenario() {
+        for text in &[
+            include_str!("../scenarios/door.room"),
+            include_str!("../scenarios/give.room"),
+            include_str!("../scenarios/kill.room"),
+            include_str!("../scenarios/ladder.room"),
+        ] {
+            assert_eq!(Scenario::parse(text).unwrap().run(), vec![]);
+        }
+
+        let scenario = Scenario::parse("object: I\nobject: the door\n\n\
+                                        do: I lock the door\ndo: I open the door\n\
+                                        expect: the door is open").unwrap();
+        assert_eq!(scenario.run(), vec![
+            Failure::ActionFailed(5, ActionError::Prevent(of_type("door"), Locked.into())),
+            Failure::Missing(6, of_type("door"), Open.into()),
+        ]);
+        assert_eq!(format!("{}", scenario.run()[1]), "line 6: expected that the door is open");
+        assert_eq!(Scenario::parse("room: I"),
+                   Err(ScenarioError::UnknownKeyword(1, "room".into())));
+        let err = Scenario::parse("object: I\ndo: I juggle the ball").unwrap_err();
+        assert_eq!(format!("{}", err), "line 2: unknown verb \"juggle\"");
+        assert_eq!(parse_statement("John was killed by Peter"),
+                   Ok((called("John"), killed_by(called("Peter")))));
+        assert_eq!(parse_statement("I picked up the key"),
+                   Ok((I, DidTo(Verb::PickUp, Box::new(of_type("key"))))));
+    }
+
+    #[test]
+    fn test_schema() {
+        let schemas = ActionSchema::parse(include_str!("../schemas/doors.actions")).unwrap();
+        let find = |name: &str| schemas.iter().find(|s| &**s.name == name).unwrap();
+        let door = of_type("door");
+        assert_eq!(find("opens").instantiate(&[I, door.clone()]), Ok(I.opens(door.clone())));
+        assert_eq!(find("closes").instantiate(&[I, door.clone()]), Ok(I.closes(door.clone())));
+        assert_eq!(find("unlocks").instantiate(&[I, door.clone()]), Ok(I.unlocks(door.clone())));
+        assert_eq!(find("gives_item").instantiate(&[He, She, It]), Ok(He.gives_item(She, It)));
+        assert_eq!(find("opens").instantiate(&[I]),
+                   Err(SchemaError::Arity {expected: 2, found: 1}));
+
+        let mut room = Room::new(vec![I, door.clone()]);
+        let knocks = find("knocks_on").instantiate(&[I, door.clone()]).unwrap();
+        room.action(&knocks).unwrap();
+        assert_eq!(format!("{}", room.objects[1]), "the door that I knocked on");
+        room.action(&I.locks(door.clone())).unwrap();
+        assert!(room.action(&knocks).is_err());
+
+        // Verbs declared in schemas are registered, so sentences can use them.
+        let mut room = Room::new(vec![I, door.clone()]);
+        ActionSchema::parse_with(include_str!("../schemas/doors.actions"), &mut room.verbs).unwrap();
+        let knock_on = room.verbs.from_name("KnockOn").unwrap();
+        assert_eq!(knock_on.past(), "knocked on");
+        assert_eq!(room.parse_action("I knock on the door"), Ok(I.does(knock_on, door.clone())));
+        assert_eq!(ActionSchema::parse_with("verb KnockOn\nforms: a, b, c, d\nend", &mut room.verbs),
+                   Err(SchemaError::DuplicateVerb(1, "KnockOn".into())));
+        assert_eq!(ActionSchema::parse("verb Eat\nend"), Err(SchemaError::MissingForms(1)));
+        let text = "verb Eat\nforms: eat, eats, ate, eaten\nend\n\
+                    verb Regurgitate\ninverse: Eat\nforms: a, b, c, d\nend";
+        let mut verbs = VerbRegistry::new();
+        assert_eq!(ActionSchema::parse_with(text, &mut verbs), Ok(vec![]));
+        let eat = verbs.from_name("Eat").unwrap();
+        assert_eq!(verbs.inverse(&eat), verbs.from_name("Regurgitate"));
+        assert_eq!(ActionSchema::parse(&format!("{}\naction eats(x)\nverb: Eat\nend", text)),
+                   Err(SchemaError::VerbArity {line: 9, expected: 2, found: 1}));
+
+        assert_eq!(ActionSchema::parse("action eats(subject, object)\nend"),
+                   Err(SchemaError::MissingVerb(1)));
+        assert_eq!(ActionSchema::parse("action eats(x, y)\nverb: Eat\nend"),
+                   Err(SchemaError::Unknown(2, "Eat".into())));
+        assert_eq!(ActionSchema::parse("action eats(x, y)\nverb: Kill\nrequire: z, Dead\nend"),
+                   Err(SchemaError::Unknown(3, "z".into())));
+    }
+
+    #[test]
+    fn test_custom_verb() {
+        let eat_info = VerbInfo {
+            name: "Eat".into(), arity: 2, inverse: None,
+            base: "eat".into(), present: "eats".into(), past: "ate".into(), participle: "eaten".into(),
+        };
+        let mut room = Room::new(vec![I, of_type("apple")]);
+        let eat = room.verbs.register(eat_info.clone());
+        let regurgitate = room.verbs.register(VerbInfo {
+            name: "Regurgitate".into(), arity: 2, inverse: Some(eat.clone()),
+            base: "regurgitate".into(), present: "regurgitates".into(),
+            past: "regurgitated".into(), participle: "regurgitated".into(),
+        });
+        assert_eq!(room.verbs.from_name("Eat"), Some(eat.clone()));
+        assert_eq!(room.verbs.register(eat_info), eat);
+        assert_eq!(room.verbs.inverse(&eat), Some(regurgitate.clone()));
+        assert_eq!(room.verbs.inverse(&Verb::Open), Some(Verb::Close));
+        assert_eq!(Verb::Give.arity(), 3);
+        assert_eq!(eat.info().participle, "eaten");
+        // Other rooms do not know the verb.
+        assert_eq!(Verb::from_name("Eat"), None);
+        assert_eq!(parse_action("I eat the apple"), Err(ParseError::UnknownVerb("eat".into())));
+
+        let apple = 1;
+        let action = room.parse_action("I eat the apple").unwrap();
+        room.action(&action).unwrap();
+        assert!(room.objects[apple].matches(&WasBy(eat.clone(), Box::new(I))));
+        assert!(!room.objects[apple].matches(&WasBy(regurgitate, Box::new(I))));
+        assert_eq!(room.parse_statement("the apple was eaten by me"),
+                   Ok((of_type("apple"), WasBy(eat.clone(), Box::new(I)))));
+        assert_eq!(format!("{}", room.objects[apple]), "the apple that I ate");
+
+        let schemas = ActionSchema::parse_with("action eats(x, y)\nverb: Eat\nend", &mut room.verbs);
+        assert_eq!(schemas.unwrap()[0].instantiate(&[I, of_type("apple")]),
+                   Ok(I.does(eat, of_type("apple"))));
+        assert_eq!(ActionSchema::parse_with("action eats(x)\nverb: Eat\nend", &mut room.verbs),
+                   Err(SchemaError::VerbArity {line: 2, expected: 2, found: 1}));
+    }
+
+    #[test]
+    fn test_custom_adjective() {
+        let mut adjectives = Adjectives::standard();
+        adjectives.exclusive(vec![adj("hot"), adj("cold"), adj("lukewarm")]);
+        assert_eq!(adjectives.excluded(&adj("hot")), vec![adj("cold"), adj("lukewarm")]);
+        assert_eq!(adjectives.excluded(&Open), vec![Closed]);
+        assert_eq!(adjectives.excluded(&Alive), vec![Dead]);
+        assert_eq!(Adjectives::standard().excluded(&adj("hot")), vec![]);
+
+        let mut stove = And(vec![of_type("stove"), adj("cold").into()]);
+        adjectives.decorate(&mut stove, adj("hot").into());
+        assert!(stove.matches(&adj("hot").into()));
+        assert!(!stove.matches(&adj("cold").into()));
+        adjectives.decorate(&mut stove, Open.into());
+        adjectives.decorate(&mut stove, Closed.into());
+        assert!(!stove.matches(&Open.into()));
+        assert_eq!(adjectives.contradictions(&stove), vec![]);
+
+        let stove = And(vec![of_type("stove"), adj("hot").into(), Open.into(),
+                             adj("cold").into(), Closed.into()]);
+        assert_eq!(adjectives.contradictions(&stove), vec![
+            (adj("hot"), adj("cold")),
+            (Open, Closed),
+        ]);
+        assert_eq!(format!("{}", stove), "the hot, open, cold, closed stove");
+
+        let mut room = Room::new(vec![I, And(vec![of_type("plant"), Alive.into()])]);
+        room.adjectives.declare(adj("shiny"));
+        assert_eq!(room.parse_statement("the stove is shiny"),
+                   Ok((of_type("stove"), adj("shiny").into())));
+        assert_eq!(room.parse_object("the shiny stove"),
+                   Ok(And(vec![of_type("stove"), adj("shiny").into()])));
+        assert_eq!(parse_statement("the stove is shiny"),
+                   Err(ParseError::ExpectedPlacement("shiny".into())));
+        room.action(&I.kills(of_type("plant"))).unwrap();
+        assert!(!room.objects[1].matches(&Alive.into()));
+        assert!(room.objects[1].matches(&Dead.into()));
+    }
+
+    #[test]
+    fn test_rules() {
+        let mut room = Room::new(vec![called("Peter"), called("John"), of_type("door")]);
+        room.rules.push(Rule::Implies(Dead.into(), has_not(of_type("door"))));
+        room.action(&called("Peter").kills(called("John"))).unwrap();
+        assert!(room.objects[0].matches(&Murderer.into()));
+        assert!(room.objects[1].has_not(of_type("door")));
+        assert_eq!(room.action(&called("John").opens(of_type("door"))),
+                   Err(ActionError::CannotAct(called("John"), Dead.into())));
+        assert_eq!(format!("{}", room.action(&called("John").opens(of_type("door"))).unwrap_err()),
+                   "John can not act because John is dead.");
+
+        // Without the background theory, locking does not close the door.
+        let mut room = Room::new(vec![I, of_type("door")]);
+        room.rules.clear();
+        room.action(&I.locks(of_type("door"))).unwrap();
+        assert!(!room.objects[1].matches(&Closed.into()));
+
+        // Rules that undo each other stop.
+        let mut room = Room::new(vec![I, And(vec![of_type("door"), Open.into()])]);
+        room.rules = vec![
+            Rule::Implies(Open.into(), Closed.into()),
+            Rule::Implies(Closed.into(), Open.into()),
+        ];
+        assert_eq!(room.apply_rules(), 3);
+        assert!(room.objects[1].matches(&Open.into()));
+        assert!(!room.objects[1].matches(&Closed.into()));
+        // Rules that agree stop as soon as nothing changes.
+        room.rules = Rule::standard();
+        assert_eq!(room.apply_rules(), 1);
+        room.objects[1].push(Locked.into());
+        assert_eq!(room.apply_rules(), 2);
+        assert!(room.objects[1].matches(&Closed.into()));
+    }
+
+    #[test]
+    fn test_consistency() {
+        let mut room = Room::new(vec![
+            And(vec![of_type("door"), Open.into(), Closed.into()]),
+            And(vec![He, has(It), has_not(It), on(of_type("roof")).into(), in_(of_type("bed")).into()]),
+            And(vec![She, Dead.into(), lean_toward(of_type("wall")).into(), on(of_type("roof")).into()]),
+        ]);
+        room.invariants.push((Dead.into(), on(of_type("roof")).into()));
+        assert_eq!(room.check_consistency(), vec![
+            Inconsistency::Exclusive(0, Open, Closed),
+            Inconsistency::HasAndHasNot(1, It),
+            Inconsistency::Placements(1, vec![on(of_type("roof")), in_(of_type("bed"))]),
+            Inconsistency::Incompatible(2, Dead.into(), on(of_type("roof")).into()),
+        ]);
+
+        let ladder = 1;
+        let mut room = Room::new(vec![
+            I,
+            And(vec![of_type("ladder"), on(of_type("ground")).into()]),
+            of_type("ground"),
+            of_type("box"),
+        ]);
+        room.strict = true;
+        let stand = of_type("ladder").stands_on(of_type("box"));
+        assert_eq!(room.action(&stand), Err(ActionError::Inconsistent(vec![
+            Inconsistency::Placements(ladder, vec![on(of_type("ground")), on(of_type("box"))]),
+        ])));
+        assert!(!room.objects[ladder].is_on(of_type("box")));
+        room.action(&I.moves(of_type("ladder"), on(of_type("box")))).unwrap();
+        assert_eq!(room.check_consistency(), vec![]);
+        room.strict = false;
+        room.action(&of_type("ladder").stands_on(of_type("ground"))).unwrap();
+        assert_eq!(room.check_consistency().len(), 1);
+
+        // Inconsistencies from before the action are not blamed on it.
+        let mut room = Room::new(vec![
+            I,
+            And(vec![of_type("door"), Open.into(), Closed.into()]),
+            of_type("box"),
+            of_type("ladder"),
+            of_type("ground"),
+        ]);
+        room.strict = true;
+        room.action(&I.talk_to(of_type("box"))).unwrap();
+        assert_eq!(room.action(&of_type("ladder").stands_on(of_type("box"))), Ok(()));
+        assert_eq!(room.action(&of_type("ladder").stands_on(of_type("ground"))),
+                   Err(ActionError::Inconsistent(vec![
+                       Inconsistency::Placements(3, vec![on(of_type("box")), on(of_type("ground"))]),
+                   ])));
+    }
+
+    #[test]
+    fn test_restrictions() {
+        let mut room = Room::new(vec![
+            I,
+            of_type("door"),
+            of_type("ladder"),
+            And(vec![called("Rex"), of_type("dog")]),
+        ]);
+        assert_eq!(room.action(&of_type("door").kills(of_type("ladder"))),
+            Err(ActionError::Restricted(of_type("door"),
+                Restriction::Subject(Verb::Kill, Feature::Animate))));
+        assert_eq!(room.action(&I.kills(of_type("ladder"))),
+            Err(ActionError::Restricted(of_type("ladder"),
+                Restriction::Object(Verb::Kill, Feature::Animate))));
+        assert!(room.action(&I.opens(of_type("ladder"))).is_err());
+        assert!(room.action(&I.picks_up(of_type("door"))).is_err());
+        // Objects of unknown kinds are not restricted.
+        room.action(&I.talk_to(called("Rex"))).unwrap();
+        room.kinds.push(Kind::new(of_type("dog"), vec![Feature::Animate]));
+        room.action(&called("Rex").talk_to(I)).unwrap();
+        assert_eq!(room.features(3), Some(vec![Feature::Animate]));
+        assert_eq!(room.features(0), Some(vec![Feature::Animate]));
+
+        let err = room.action(&I.kills(of_type("ladder"))).unwrap_err();
+        assert_eq!(format!("{}", err),
+                   "The ladder can not be killed because the ladder is not animate.");
+        let err = room.action(&of_type("door").kills(I)).unwrap_err();
+        assert_eq!(format!("{}", err), "The door can not kill because the door is not animate.");
+    }
+
+    #[test]
+    fn test_life_states() {
+        let (he, she) = (1, 2);
+        let mut room = Room::new(vec![of_type("bed"), He, She, of_type("door")]);
+        assert!(room.action(&He.wakes_up_in(of_type("bed"))).is_err());
+        room.action(&He.sleeps_in(of_type("bed"))).unwrap();
+        assert!(room.objects[he].matches(&Asleep.into()));
+        assert_eq!(room.action(&He.opens(of_type("door"))),
+                   Err(ActionError::CannotAct(He, Asleep.into())));
+        assert_eq!(room.action(&She.talk_to(He)),
+                   Err(ActionError::Unresponsive(He, Asleep.into())));
+        assert_eq!(format!("{}", room.action(&She.talk_to(He)).unwrap_err()),
+                   "He can not respond because he is asleep.");
+        room.action(&He.wakes_up_in(of_type("bed"))).unwrap();
+        assert!(room.objects[he].matches(&Awake.into()));
+        assert!(!room.objects[he].matches(&Asleep.into()));
+        room.action(&She.talk_to(He)).unwrap();
+        room.action(&He.opens(of_type("door"))).unwrap();
+
+        room.objects[she].push(Unconscious.into());
+        assert_eq!(room.action(&She.closes(of_type("door"))),
+                   Err(ActionError::CannotAct(She, Unconscious.into())));
+        room.action(&He.kills(She)).unwrap();
+        assert!(!room.objects[she].matches(&Unconscious.into()));
+        assert_eq!(room.action(&He.talk_to(She)),
+                   Err(ActionError::Unresponsive(She, Dead.into())));
+        assert_eq!(parse_statement("she is unconscious"), Ok((She, Unconscious.into())));
+    }
+
+    #[test]
+    fn test_taxonomy() {
+        let taxonomy = Taxonomy::parse(include_str!("../types/household.types")).unwrap();
+        assert_eq!(taxonomy.relations[0],
+                   (Arc::new("bed".into()), Arc::new("piece of furniture".into())));
+        assert!(taxonomy.subtype_of("bed", "thing"));
+        assert!(!taxonomy.subtype_of("thing", "bed"));
+        assert!(taxonomy.matches(&of_type("box"), &of_type("container")));
+        assert!(!taxonomy.matches(&of_type("container"), &of_type("box")));
+        // Without a taxonomy, types match exactly.
+        assert!(!of_type("box").matches(&of_type("container")));
+
+        let mut room = Room::new(vec![I, of_type("bed"), And(vec![of_type("box"), Open.into()])]);
+        assert_eq!(room.find(&of_type("piece of furniture")), Err(vec![]));
+        room.taxonomy = taxonomy;
+        assert_eq!(room.find(&of_type("piece of furniture")), Ok(1));
+        assert_eq!(room.find(&And(vec![of_type("thing"), Open.into()])), Ok(2));
+        assert_eq!(room.find(&of_type("thing")), Err(vec![1, 2]));
+        assert_eq!(room.ask("Is the container open?"), Ok(Answer::Yes));
+        // Kinds apply to sub-types.
+        room.kinds.push(Kind::new(of_type("container"), vec![Feature::Openable]));
+        room.action(&I.closes(of_type("box"))).unwrap();
+
+        // Cycles do not loop forever.
+        let mut cycle = Taxonomy::new();
+        cycle.is_a("egg", "chicken");
+        cycle.is_a("chicken", "egg");
+        assert!(cycle.subtype_of("egg", "chicken"));
+        assert!(!cycle.subtype_of("egg", "rooster"));
+
+        assert_eq!(Taxonomy::parse("a bed"), Err(TaxonomyError::Syntax(1, "a bed".into())));
+    }
+
+    #[test]
+    fn test_refer() {
+        let mut room = Room::new(vec![
+            I,
+            of_type("door"),
+            And(vec![of_type("door"), Locked.into()]),
+            And(vec![called("Peter"), He, Dead.into()]),
+            And(vec![He, Open.into(), on(of_type("roof")).into()]),
+            And(vec![He, on(of_type("roof")).into(), Dead.into()]),
+            of_type("door"),
+        ]);
+        assert_eq!(room.refer(0), Some(I));
+        assert_eq!(room.refer(2), Some(And(vec![of_type("door"), Locked.into()])));
+        assert_eq!(room.refer(3), Some(called("Peter")));
+        assert_eq!(room.refer(4), Some(And(vec![He, Open.into()])));
+        assert_eq!(room.refer(5), Some(And(vec![He, on(of_type("roof")).into(), Dead.into()])));
+        // Two plain doors can not be told apart.
+        assert_eq!(room.refer(1), None);
+        room.objects.pop();
+        assert_eq!(room.refer(1), None);
+        room.objects[1].push(Open.into());
+        assert_eq!(room.refer(1), Some(And(vec![of_type("door"), Open.into()])));
+        for i in 0..room.objects.len() {
+            assert_eq!(room.find(&room.refer(i).unwrap()), Ok(i));
+        }
+        assert_eq!(format!("{}", room.refer(2).unwrap()), "the locked door");
+    }
+
+    #[test]
+    fn test_clarify() {
+        let mut room = Room::new(vec![
+            I,
+            And(vec![of_type("door"), Locked.into()]),
+            And(vec![of_type("door"), Open.into()]),
+            And(vec![called("Peter"), He]),
+            And(vec![called("John"), He]),
+        ]);
+        let action = I.closes(of_type("door"));
+        let err = room.action(&action).unwrap_err();
+        let question = room.clarify(&err).unwrap();
+        assert_eq!(question.candidates, vec![(1, Locked.into()), (2, Open.into())]);
+        assert_eq!(format!("{}", question), "Which door: the locked one or the open one?");
+        let reply = parse_object("the open one").unwrap();
+        assert_eq!(question.answer(&room, &reply), Ok(2));
+        assert_eq!(question.answer(&room, &Any), Err(ActionError::Ambiguous(of_type("door"), vec![1, 2])));
+        assert_eq!(question.answer(&room, &Dead.into()), Err(ActionError::NotFound(Dead.into())));
+        let action = question.resolve(&room, &action, &reply).unwrap();
+        assert_eq!(action, I.closes(And(vec![of_type("door"), Open.into()])));
+        room.action(&action).unwrap();
+
+        let err = room.action(&I.talk_to(He)).unwrap_err();
+        let question = room.clarify(&err).unwrap();
+        assert_eq!(format!("{}", question), "Who do you mean: Peter or John?");
+        assert_eq!(question.answer(&room, &called("John")), Ok(4));
+        assert_eq!(room.clarify(&ActionError::NotFound(He)), None);
+    }
+
+    #[test]
+    fn test_discourse() {
+        let (peter, sheila, key) = (0, 1, 2);
+        let mut room = Room::new(vec![]);
+        let ids = [
+            room.add(And(vec![called("Peter"), He])),
+            room.add(And(vec![called("Sheila"), She])),
+            room.add(of_type("key")),
+            room.add(of_type("ladder")),
+        ];
+        let mut discourse = Discourse::new();
+        assert_eq!(discourse.resolve(&room, &He.talk_to(It)), He.talk_to(It));
+        discourse.action(&mut room, &parse_action("Peter took the key").unwrap()).unwrap();
+        assert_eq!(discourse.mentioned, vec![ids[peter], ids[key]]);
+        discourse.action(&mut room, &parse_action("He gave it to Sheila").unwrap()).unwrap();
+        assert!(room.objects[sheila].has(of_type("key")));
+        assert!(room.objects[peter].has_not(of_type("key")));
+        assert_eq!(discourse.mentioned, vec![ids[peter], ids[sheila], ids[key]]);
+        assert_eq!(discourse.antecedent(&room, &She), Some(sheila));
+        assert_eq!(discourse.antecedent(&room, &That), Some(key));
+        assert_eq!(discourse.antecedent(&room, &I), None);
+
+        // Animate objects are not referred to by "it".
+        discourse.action(&mut room, &She.carries(of_type("ladder"))).unwrap();
+        room.objects[sheila].remove(&She);
+        room.objects[sheila].push(of_type("person"));
+        assert_eq!(discourse.antecedent(&room, &It), Some(3));
+        discourse.mention(ids[sheila]);
+        assert_eq!(discourse.antecedent(&room, &It), Some(3));
+        assert_eq!(discourse.antecedent(&room, &She), None);
+    }
+
+    #[test]
+    fn test_ids() {
+        let mut room = Room::new(vec![of_type("bed")]);
+        let me = room.add(I);
+        let door = room.add(And(vec![of_type("door"), Open.into()]));
+        assert_eq!(room.index(door), Some(2));
+        assert_eq!(room.id(0), room.id(0));
+        assert_eq!(room.find(&And(vec![of_type("door"), Open.into()])), Ok(2));
+
+        let close = room.resolve(&I.closes(And(vec![of_type("door"), Open.into()]))).unwrap();
+        let Action::Do {ref subject, ref object, ..} = close;
+        assert_eq!((subject, object), (&Object::Id(me), &Object::Id(door)));
+        room.action(&close).unwrap();
+        // The description no longer matches, but the resolved action does.
+        assert!(room.action(&I.closes(And(vec![of_type("door"), Open.into()]))).is_err());
+        room.action(&close).unwrap();
+        assert!(room.holds(&I, &DidTo(Verb::Close, Box::new(of_type("door")))));
+        assert_eq!(format!("{}", room.objects[2]), "the closed door that I closed");
+
+        // Removing objects does not break ids.
+        let bed = room.id(0);
+        assert_eq!(room.remove(bed), Some(of_type("bed")));
+        assert_eq!(room.remove(bed), None);
+        let other = room.add(of_type("door"));
+        assert!(other != door);
+        assert_eq!(room.index(door), Some(1));
+        room.action(&close).unwrap();
+        assert_eq!(room.objects.len(), 3);
+        // The history tells the doors apart once "the door" is ambiguous.
+        let closed_door = And(vec![of_type("door"), Closed.into()]);
+        assert!(room.holds(&I, &DidTo(Verb::Close, Box::new(closed_door))));
+    }
+
+    #[test]
+    fn test_query() {
+        let mut room = Room::new(vec![
+            called("Peter"),
+            called("John"),
+            And(vec![of_type("ladder"), on(of_type("ground")).into()]),
+            And(vec![of_type("box"), on(of_type("ground")).into(), Open.into()]),
+            of_type("ground"),
+        ]);
+        room.action(&called("Peter").kills(called("John"))).unwrap();
+        room.objects[0].push(has(of_type("key")));
+
+        let who = room.query(&And(vec![var("who"), killed(called("John"))]));
+        assert_eq!(who.len(), 1);
+        assert_eq!(who[0].get("who"), Some(&called("Peter")));
+        let by = room.query(&killed_by(var("x")));
+        assert_eq!(by.len(), 1);
+        assert_eq!(by[0].get("x"), Some(&called("Peter")));
+        let what: Vec<Object> = room.query(&And(vec![var("what"), on(of_type("ground")).into()]))
+            .iter().map(|b| b.get("what").unwrap().clone()).collect();
+        assert_eq!(what, vec![of_type("ladder"), of_type("box")]);
+        let items = room.query(&has(var("item")));
+        assert_eq!(items[0].get("item"), Some(&of_type("key")));
+        assert_eq!(room.query(&And(vec![var("x"), Dead.into()]))[0].get("x"), Some(&called("John")));
+        assert!(room.query(&And(vec![var("x"), Locked.into()])).is_empty());
+
+        // A variable that occurs twice must have the same value.
+        let same = And(vec![on(var("p")).into(), lean_toward(var("p")).into()]);
+        assert!(room.query(&same).is_empty());
+        room.objects[2].push(lean_toward(of_type("ground")).into());
+        let found = room.query(&same);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].apply(&lean_toward(var("p")).into()), lean_toward(of_type("ground")).into());
+    }
+
+    #[test]
+    fn test_qa() {
+        let mut room = Room::new(vec![
+            And(vec![called("Peter"), He]),
+            And(vec![called("John"), He]),
+            And(vec![called("Sheila"), She, has(of_type("key")), has_not(of_type("ladder"))]),
+            And(vec![of_type("door"), Locked.into()]),
+            And(vec![of_type("ladder"), on(of_type("ground")).into(), lean_toward(of_type("wall")).into()]),
+            of_type("ground"),
+            of_type("wall"),
+            of_type("box"),
+        ]);
+        room.action(&called("Peter").kills(called("John"))).unwrap();
+
+        let ask = |text: &str| format!("{}", room.ask(text).unwrap());
+        assert_eq!(ask("Who killed John?"), "Peter.");
+        assert_eq!(ask("Who was killed by Peter?"), "John.");
+        assert_eq!(ask("Whom did Peter kill?"), "John.");
+        assert_eq!(ask("Who did Peter kill?"), "John.");
+        assert_eq!(ask("Is the door locked?"), "Yes.");
+        assert_eq!(ask("Is the door unlocked?"), "No.");
+        assert_eq!(ask("Is the door open?"), "No.");
+        assert_eq!(ask("Is the box open?"), "I do not know.");
+        assert_eq!(ask("Is John dead?"), "Yes.");
+        assert_eq!(ask("Is Peter a murderer?"), "Yes.");
+        assert_eq!(ask("Did Peter kill John?"), "Yes.");
+        assert_eq!(ask("Did John kill Peter?"), "No.");
+        assert_eq!(ask("What does Sheila have?"), "The key.");
+        assert_eq!(ask("Does Sheila have the key?"), "Yes.");
+        assert_eq!(ask("Does Sheila have the ladder?"), "No.");
+        assert_eq!(ask("Does Peter have the ladder?"), "I do not know.");
+        assert_eq!(ask("Where is the ladder?"), "On the ground and leaning toward the wall.");
+        assert_eq!(ask("Where is the box?"), "I do not know.");
+        assert_eq!(ask("What is on the ground?"), "The ladder.");
+        assert_eq!(ask("Who has the key?"), "Sheila.");
+        assert_eq!(ask("Is the ladder on the wall?"), "No.");
+        assert_eq!(parse_question("Is the door locked?"),
+                   Ok(Question::YesNo(of_type("door"), Locked.into())));
+        assert_eq!(room.ask("Where the ladder?"), Err(ParseError::Expected("is", "the".into())));
+        assert_eq!(room.ask("Whom?"), Err(ParseError::UnexpectedEnd));
+        assert_eq!(room.ask("Who did Peter kill John?"),
+                   Err(ParseError::UnusedQuestionWord("who".into())));
+        assert!(parse_action("who opens the door").is_err());
+        assert!(parse_statement("who is dead").is_err());
+    }
+
+    #[test]
+    fn test_babi() {
+        let task = BabiTask::parse("qa1", include_str!("../babi/qa1_locations.txt")).unwrap();
+        assert_eq!(task.stories.len(), 2);
+        assert_eq!(task.stories[0][2],
+                   BabiLine::Question("Where is Mary?".into(), vec!["bathroom".into()]));
+        let result = task.run();
+        assert_eq!(result, BabiResult {correct: 5, total: 5, skipped: 0});
+        assert_eq!(result.accuracy(), 1.0);
+
+        let task = BabiTask::parse("qa2", include_str!("../babi/qa2_items.txt")).unwrap();
+        assert_eq!(task.run(), BabiResult {correct: 3, total: 3, skipped: 0});
+        let task = BabiTask::parse("qa6", include_str!("../babi/qa6_yes_no.txt")).unwrap();
+        assert_eq!(task.run(), BabiResult {correct: 3, total: 3, skipped: 0});
+
+        let task = BabiTask::parse("other", "1 Mary sang a song.\n2 Where is Mary?\tkitchen\t1").unwrap();
+        assert_eq!(task.run(), BabiResult {correct: 0, total: 1, skipped: 1});
+        assert_eq!(BabiTask::parse("bad", "Mary went to the kitchen."),
+                   Err(BabiError::Syntax(1, "Mary went to the kitchen.".into())));
+        assert_eq!(format!("{}", BabiError::Syntax(1, "Mary went to the kitchen.".into())),
+                   "line 1: expected a line number in \"Mary went to the kitchen.\"");
+        let task = BabiTask::parse("qa10", "1 John went to the kitchen.\n\
+                                            2 Is the key in the kitchen?\tmaybe\t1").unwrap();
+        assert_eq!(task.run(), BabiResult {correct: 0, total: 1, skipped: 0});
+        assert_eq!(parse_action("John went back to the hallway"),
+                   Ok(called("John").goes_to(of_type("hallway"))));
+        assert_eq!(parse_action("John moved back to the hallway"),
+                   Ok(called("John").goes_to(of_type("hallway"))));
+        assert_eq!(parse_action("John moved to the hallway"),
+                   Ok(called("John").goes_to(of_type("hallway"))));
+        assert_eq!(parse_action("John moved back the hallway"),
+                   Err(ParseError::Expected("to", "the".into())));
+        assert!(parse_action("John moved to to the hallway").is_err());
+    }
+
+    #[test]
+    fn test_dataset() {
+        let records = Generator::new(7).generate();
+        assert_eq!(records.len(), 50);
+        assert_eq!(records, Generator::new(7).generate());
+        assert!(records != Generator::new(8).generate());
+        assert!(records.iter().any(|r| r.error.is_none()));
+        assert!(records.iter().any(|r| r.error.is_some()));
+        for r in &records {
+            if r.error.is_some() {assert_eq!(r.before, r.after)};
+            let json = r.to_json();
+            assert!(json.starts_with(&format!("{{\"room\":{},\"step\":{},", r.room, r.step)));
+            assert!(!json.contains('\n'));
+        }
+        let ok = records.iter().find(|r| r.error.is_none()).unwrap();
+        assert!(ok.to_json().contains("\"success\":true,\"error\":null,\"reason\":null"));
+        let failed = records.iter().find(|r| r.error.is_some()).unwrap();
+        assert!(failed.to_csv().starts_with(&format!("{},{},\"", failed.room, failed.step)));
+        assert_eq!(Record::CSV_HEADER.split(',').count(), 10);
+
+        assert!(records.iter().all(|r| r.prose.is_none()));
+        let record = |action: Action| Record {
+            room: 0, step: 0, before: vec![], action, error: None, after: vec![], prose: None,
+        };
+        let give = record(I.gives_item(called("Peter"), key_to(of_type("door")))).to_json();
+        assert!(give.contains("\"action\":{\"subject\":\"I\",\"verb\":\"Give\",\
+                               \"object\":{\"Called\":\"Peter\"},\
+                               \"other\":{\"KeyTo\":{\"OfType\":\"door\"}}},\
+                               \"action_text\":\"I gave the key to the door to Peter.\""));
+        let moves = record(I.moves(of_type("ladder"), on(of_type("roof")))).to_json();
+        assert!(moves.contains("\"other\":{\"On\":{\"OfType\":\"roof\"}}}"));
+        assert!(record(I.opens(of_type("door"))).to_json().contains("\"other\":null}"));
+        assert!(ok.to_json().contains("{\"KeyTo\":{\"OfType\":\"door\"}}"));
+        assert!(!ok.to_json().contains("before_text"));
+
+        let generator = Generator {seed: 1, rooms: 2, steps: 3, prose: true};
+        let records = generator.generate();
+        assert_eq!(records.len(), 6);
+        let (ref before, ref after) = *records[0].prose.as_ref().unwrap();
+        assert!(records[0].to_json().contains(&format!("\"before_text\":\"{}\"", before)));
+        assert!(records[0].to_csv().ends_with(&format!(",\"{}\",\"{}\"", before, after)));
+    }
+
+    #[test]
+    fn test_learn() {
+        let door = |props: Vec<Object>| {
+            let mut door = of_type("door");
+            for p in props {door.push(p)};
+            door
+        };
+        let mut observations = vec![];
+        for (states, has_key) in [
+            (vec![Closed.into()], false),
+            (vec![Open.into()], true),
+            (vec![Locked.into()], true),
+            (vec![Locked.into()], false),
+            (vec![Unlocked.into()], false),
+        ] {
+            for &unlock in &[false, true] {
+                let me = if has_key {And(vec![I, has(key_to(of_type("door")))])} else {I};
+                let mut room = Room::new(vec![me, door(states.clone())]);
+                room.apply_rules();
+                let action = if unlock {I.unlocks(of_type("door"))} else {I.opens(of_type("door"))};
+                observations.push(room.observe(&action).unwrap());
+            }
+        }
+        let mut room = Room::new(vec![I, of_type("door")]);
+        observations.push(room.observe(&I.opens(I)).unwrap());
+
+        let opens = ActionSchema::learn("opens", Verb::Open, &observations);
+        let Action::Do {ref decorate, ref remove, ref require, ref prevent, ref distinct, ..} = opens.action;
+        assert_eq!(decorate, &vec![(var("object"), Open.into())]);
+        assert_eq!(remove, &vec![(var("object"), Closed.into())]);
+        assert_eq!(require, &vec![]);
+        assert_eq!(prevent, &vec![(var("object"), Locked.into())]);
+        assert_eq!(distinct, &vec![var("subject"), var("object")]);
+
+        let unlocks = ActionSchema::learn("unlocks", Verb::Unlock, &observations);
+        let Action::Do {ref decorate, ref remove, ref require, ..} = unlocks.action;
+        assert_eq!(decorate, &vec![(var("object"), Unlocked.into())]);
+        assert_eq!(remove, &vec![(var("object"), Locked.into())]);
+        assert_eq!(require, &vec![(var("subject"), has(key_to(var("object"))))]);
+
+        // The learned action works like the one written by hand.
+        let action = unlocks.instantiate(&[I, of_type("door")]).unwrap();
+        let mut room = Room::new(vec![I, door(vec![Locked.into()])]);
+        assert!(room.action(&action).is_err());
+        room.objects[0].push(has(key_to(of_type("door"))));
+        room.action(&action).unwrap();
+        assert!(room.objects[1].matches(&Unlocked.into()));
+
+        // Two constraints explain every failure, although "a" explains the most failures.
+        let observe = |props: &[&str], after: Option<Vec<Object>>| {
+            let mut door = of_type("door");
+            for p in props {door.push(adj(p).into())};
+            Observation {before: vec![I, door], verb: Verb::Open, subject: 0, object: 1, after}
+        };
+        let observations = vec![
+            observe(&[], Some(vec![I, And(vec![of_type("door"), Open.into()])])),
+            observe(&["a", "b"], None),
+            observe(&["a", "c"], None),
+            observe(&["a", "b"], None),
+            observe(&["a", "c"], None),
+            observe(&["b"], None),
+            observe(&["c"], None),
+        ];
+        let opens = ActionSchema::learn("opens", Verb::Open, &observations);
+        let Action::Do {ref require, ref prevent, ..} = opens.action;
+        assert_eq!(require, &vec![]);
+        assert_eq!(prevent, &vec![(var("object"), adj("b").into()), (var("object"), adj("c").into())]);
+
+        // Many failures with different reasons are learned quickly.
+        let names: Vec<String> = (0..40).map(|i| format!("p{}", i)).collect();
+        let mut observations = vec![observe(&[], Some(vec![I, And(vec![of_type("door"), Open.into()])]))];
+        observations.extend(names.iter().map(|name| observe(&[name], None)));
+        let opens = ActionSchema::learn("opens", Verb::Open, &observations);
+        let Action::Do {ref prevent, ..} = opens.action;
+        assert_eq!(prevent.len(), 40);
+    }
+
+    #[test]
+    fn test_ablation() {
+        let scenarios: Vec<(String, Scenario)> = vec![
+            ("door.room", include_str!("../scenarios/door.room")),
+            ("give.room", include_str!("../scenarios/give.room")),
+        ].into_iter().map(|(name, text)| (name.to_string(), Scenario::parse(text).unwrap())).collect();
+        let ablations = Ablation::all();
+        let prevent_locked = ablations.iter()
+            .find(|a| a.verb == Verb::Open && a.constraint == Constraint::Prevent)
+            .unwrap();
+        assert_eq!(prevent_locked.description, "Open: prevent that object is locked");
+        assert_eq!(prevent_locked.apply(&I.opens(of_type("door"))), Action::Do {
+            subject: I, verb: Verb::Open, object: of_type("door"),
+            decorate: vec![(of_type("door"), Open.into())],
+            remove: vec![(of_type("door"), Closed.into())],
+            remove_placement: vec![],
+            require: vec![],
+            prevent: vec![],
+            distinct: vec![I, of_type("door")],
+        });
+        assert_eq!(prevent_locked.apply(&I.closes(of_type("door"))), I.closes(of_type("door")));
+        // Actions from schemas with the same verb keep their constraints.
+        let mut custom = I.does(Verb::Open, of_type("door"));
+        let Action::Do {ref mut prevent, ..} = custom;
+        prevent.push((I, Dead.into()));
+        assert_eq!(prevent_locked.apply(&custom), custom);
+        assert_eq!(prevent_locked.constructor, "opens");
+        let distinct_place = ablations.iter()
+            .find(|a| a.description == "Climb: distinct on other")
+            .unwrap();
+        let Action::Do {ref distinct, ..} = distinct_place.apply(&I.climbs_into(of_type("bed")));
+        assert_eq!(distinct, &vec![I, of_type("bed")]);
+
+        let reports = ablate(&scenarios);
+        assert_eq!(reports.len(), ablations.len());
+        let broken = |description: &str| reports.iter()
+            .find(|r| r.ablation.description == description).unwrap().broken.clone();
+        assert_eq!(broken("Open: prevent that object is locked"),
+                   vec![("door.room".to_string(), Failure::ActionSucceeded(9))]);
+        assert_eq!(broken("Unlock: require that subject has the key to object"),
+                   vec![("door.room".to_string(), Failure::ActionSucceeded(10))]);
+        assert_eq!(broken("Give: distinct object")[0],
+                   ("give.room".to_string(), Failure::ActionSucceeded(6)));
+        // Opening removes closed anyway, since the adjectives exclude each other.
+        assert!(broken("Open: remove that object is closed").is_empty());
+        let masked = |description: &str| ablations.iter()
+            .find(|a| a.description == description).unwrap().masked_by.clone();
+        assert_eq!(masked("Open: remove that object is closed"), Some("object is open".into()));
+        assert_eq!(masked("PutDown: remove that subject has object"), None);
+        let table = ablation_table(&reports);
+        assert!(table.starts_with("| constraint | broken | expectations | masked by |\n\
+                                   |---|---|---|---|\n"));
+        assert!(table.contains("| Open: prevent that object is locked | 1 | door.room line 9: \
+                                expected failure, but succeeded |  |"));
+        assert!(table.contains("| Open: remove that object is closed | 0 |  | object is open |"));
+    }
+
     #[test]
     fn test_chess() {
         let i = 1;
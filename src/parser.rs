@@ -0,0 +1,520 @@
+use *;
+
+use std::fmt;
+
+/// Describes why a sentence could not be parsed.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParseError {
+    /// The sentence ended before it was complete.
+    UnexpectedEnd,
+    /// Expected a noun phrase, found another word.
+    ExpectedNounPhrase(String),
+    /// Expected a specific word, found another word.
+    Expected(&'static str, String),
+    /// Expected a placement such as "on" or "into", found another word.
+    ExpectedPlacement(String),
+    /// The verb is not known.
+    UnknownVerb(String),
+    /// There are words left after the end of the sentence.
+    Trailing(String),
+    /// The question word has no place in the question, e.g. "who did Peter kill John".
+    UnusedQuestionWord(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        use ParseError::*;
+
+        match *self {
+            UnexpectedEnd => write!(w, "the sentence ended too early"),
+            ExpectedNounPhrase(ref word) => {
+                write!(w, "expected something like \"the door\" or a name, found \"{}\"", word)
+            }
+            Expected(expected, ref word) if word.is_empty() => {
+                write!(w, "expected \"{}\", found the end of the sentence", expected)
+            }
+            Expected(expected, ref word) => write!(w, "expected \"{}\", found \"{}\"", expected, word),
+            ExpectedPlacement(ref word) => {
+                write!(w, "expected a place such as \"on\" or \"into\", found \"{}\"", word)
+            }
+            UnknownVerb(ref word) => write!(w, "unknown verb \"{}\"", word),
+            Trailing(ref word) => write!(w, "unexpected \"{}\" after the end of the sentence", word),
+            UnusedQuestionWord(ref word) => {
+                write!(w, "the question word \"{}\" does not fit in the question", word)
+            }
+        }
+    }
+}
+
+/// Parses a simple English sentence into an action.
+///
+/// For example "I climb the ladder onto the roof" or "Peter gives the key to her".
+pub fn parse_action(text: &str) -> Result<Action, ParseError> {
+    Parser::new(text, &VerbRegistry::new(), &Adjectives::standard()).parse(Parser::action)
+}
+
+/// Parses a noun phrase into an object.
+///
+/// For example "the locked door", "Peter" or "the key to the door".
+pub fn parse_object(text: &str) -> Result<Object, ParseError> {
+    Parser::new(text, &VerbRegistry::new(), &Adjectives::standard()).parse(|parser| parser.noun_phrase(true))
+}
+
+/// Parses a simple English statement into an object and a property.
+///
+/// For example "the door is locked", "I have the key" or "John was killed by Peter".
+pub fn parse_statement(text: &str) -> Result<(Object, Object), ParseError> {
+    Parser::new(text, &VerbRegistry::new(), &Adjectives::standard()).parse(Parser::statement)
+}
+
+/// Parses a simple English question.
+///
+/// For example "Who killed John?", "Is the door locked?",
+/// "What does Sheila have?" or "Where is the ladder?".
+pub fn parse_question(text: &str) -> Result<Question, ParseError> {
+    Parser::new(text, &VerbRegistry::new(), &Adjectives::standard()).parse(Parser::question)
+}
+
+impl Room {
+    /// Parses a sentence into an action, using the vocabulary of the room.
+    ///
+    /// The vocabulary is the custom verbs in `Room::verbs` and custom adjectives in `Room::adjectives`.
+    pub fn parse_action(&self, text: &str) -> Result<Action, ParseError> {
+        Parser::new(text, &self.verbs, &self.adjectives).parse(Parser::action)
+    }
+
+    /// Parses a noun phrase into an object, using the vocabulary of the room.
+    pub fn parse_object(&self, text: &str) -> Result<Object, ParseError> {
+        Parser::new(text, &self.verbs, &self.adjectives).parse(|parser| parser.noun_phrase(true))
+    }
+
+    /// Parses a statement into an object and a property, using the vocabulary of the room.
+    pub fn parse_statement(&self, text: &str) -> Result<(Object, Object), ParseError> {
+        Parser::new(text, &self.verbs, &self.adjectives).parse(Parser::statement)
+    }
+
+    /// Parses a question, using the vocabulary of the room.
+    pub fn parse_question(&self, text: &str) -> Result<Question, ParseError> {
+        Parser::new(text, &self.verbs, &self.adjectives).parse(Parser::question)
+    }
+}
+
+/// Verb forms in present, third person and past tense, mapped to their base form.
+const VERBS: &[(&str, &[&str])] = &[
+    ("carry", &["carry", "carries", "carried"]),
+    ("climb", &["climb", "climbs", "climbed"]),
+    ("close", &["close", "closes", "closed"]),
+    ("discard", &["discard", "discards", "discarded"]),
+    ("drop", &["drop", "drops", "dropped"]),
+    ("get", &["get", "gets", "got"]),
+    ("give", &["give", "gives", "gave"]),
+    ("go", &["go", "goes", "went"]),
+    ("grab", &["grab", "grabs", "grabbed"]),
+    ("journey", &["journey", "journeys", "journeyed"]),
+    ("kill", &["kill", "kills", "killed"]),
+    ("lean", &["lean", "leans", "leaned", "leant"]),
+    ("lock", &["lock", "locks", "locked"]),
+    ("move", &["move", "moves", "moved"]),
+    ("open", &["open", "opens", "opened"]),
+    ("pick", &["pick", "picks", "picked"]),
+    ("play", &["play", "plays", "played"]),
+    ("put", &["put", "puts"]),
+    ("sleep", &["sleep", "sleeps", "slept"]),
+    ("stand", &["stand", "stands", "stood"]),
+    ("take", &["take", "takes", "took"]),
+    ("talk", &["talk", "talks", "talked"]),
+    ("travel", &["travel", "travels", "travelled", "traveled"]),
+    ("unlock", &["unlock", "unlocks", "unlocked"]),
+    ("wake", &["wake", "wakes", "woke"]),
+    ("walk", &["walk", "walks", "walked"]),
+];
+
+/// Built-in adjectives that can be used in noun phrases.
+const ADJECTIVES: &[(&str, Adjective)] = &[
+    ("dead", Adjective::Dead),
+    ("open", Adjective::Open),
+    ("closed", Adjective::Closed),
+    ("locked", Adjective::Locked),
+    ("unlocked", Adjective::Unlocked),
+    ("asleep", Adjective::Asleep),
+    ("awake", Adjective::Awake),
+    ("unconscious", Adjective::Unconscious),
+    ("alive", Adjective::Alive),
+];
+
+struct Parser<'a> {
+    words: Vec<&'a str>,
+    pos: usize,
+    verbs: &'a VerbRegistry,
+    adjectives: &'a Adjectives,
+    /// Fills a missing noun phrase at the end of a question, e.g. "what does Sheila have".
+    gap: Option<Object>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(text: &'a str, verbs: &'a VerbRegistry, adjectives: &'a Adjectives) -> Parser<'a> {
+        let words = text.split(|c: char| c.is_whitespace() || ".,!?".contains(c))
+            .filter(|w| !w.is_empty())
+            .collect();
+        Parser {words, pos: 0, verbs, adjectives, gap: None}
+    }
+
+    /// Finds a built-in or declared custom adjective.
+    fn adjective(&self, word: &str) -> Option<Adjective> {
+        ADJECTIVES.iter().find(|&&(name, _)| name == word).map(|(_, adj)| adj.clone())
+            .or_else(|| self.adjectives.find(word))
+    }
+
+    /// Reads the whole text with a parsing function.
+    fn parse<T, F>(mut self, f: F) -> Result<T, ParseError>
+        where F: FnOnce(&mut Parser<'a>) -> Result<T, ParseError>
+    {
+        let res = f(&mut self)?;
+        self.end()?;
+        Ok(res)
+    }
+
+    fn peek(&self) -> Option<String> {
+        self.words.get(self.pos).map(|w| w.to_lowercase())
+    }
+
+    fn next(&mut self) -> Result<String, ParseError> {
+        let word = self.peek().ok_or(ParseError::UnexpectedEnd)?;
+        self.pos += 1;
+        Ok(word)
+    }
+
+    fn eat(&mut self, word: &str) -> bool {
+        if self.peek().as_deref() == Some(word) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, word: &'static str) -> Result<(), ParseError> {
+        if self.eat(word) {Ok(())}
+        else {Err(ParseError::Expected(word, self.peek().unwrap_or_default()))}
+    }
+
+    fn end(&self) -> Result<(), ParseError> {
+        match self.peek() {
+            None => Ok(()),
+            Some(word) => Err(ParseError::Trailing(word)),
+        }
+    }
+
+    fn noun_phrase(&mut self, allow_key_to: bool) -> Result<Object, ParseError> {
+        if self.pos == self.words.len() {
+            if let Some(gap) = self.gap.take() {return Ok(gap)};
+        }
+        let original = *self.words.get(self.pos).ok_or(ParseError::UnexpectedEnd)?;
+        let word = self.next()?;
+        let pronoun = match &*word {
+            "i" | "me" | "myself" => Some(Object::I),
+            "you" => Some(Object::You),
+            "he" | "him" => Some(Object::He),
+            "she" | "her" => Some(Object::She),
+            "it" => Some(Object::It),
+            "that" | "this" => Some(Object::That),
+            _ => None,
+        };
+        if let Some(obj) = pronoun {return Ok(obj)};
+        if word != "the" && word != "a" && word != "an" {
+            if original.starts_with(char::is_uppercase) {
+                return Ok(called(original));
+            }
+            return Err(ParseError::ExpectedNounPhrase(word));
+        }
+
+        let mut adjectives = vec![];
+        while let Some(adj) = self.peek().and_then(|w| self.adjective(&w)) {
+            adjectives.push(adj.into());
+            self.pos += 1;
+        }
+        let noun = self.next()?;
+        if noun == "one" {
+            // Refers to an object by its adjectives alone, e.g. "the locked one".
+            return Ok(match adjectives.len() {
+                0 => Object::Any,
+                1 => adjectives.pop().unwrap(),
+                _ => Object::And(adjectives),
+            });
+        }
+        let noun = if allow_key_to && noun == "key" && self.eat("to") {
+            key_to(self.noun_phrase(true)?)
+        } else {
+            of_type(&noun)
+        };
+        if adjectives.is_empty() {Ok(noun)}
+        else {
+            adjectives.insert(0, noun);
+            Ok(Object::And(adjectives))
+        }
+    }
+
+    fn placement(&mut self) -> Result<Placement, ParseError> {
+        let word = self.next()?;
+        let f: fn(Object) -> Placement = match &*word {
+            "on" | "onto" | "upon" => on,
+            "in" | "into" | "inside" => in_,
+            "toward" | "towards" | "against" => lean_toward,
+            "out" => {
+                self.expect("of")?;
+                out_of
+            }
+            _ => return Err(ParseError::ExpectedPlacement(word)),
+        };
+        Ok(f(self.noun_phrase(true)?))
+    }
+
+    /// Reads a verb with particles, e.g. "picked up", using a form of the verbs.
+    fn verb_form(&mut self, verbs: &[Verb], form: fn(&Verb) -> &str) -> Option<Verb> {
+        let mut best: Option<(Verb, usize)> = None;
+        for verb in verbs {
+            let words: Vec<&str> = form(verb).split(' ').collect();
+            let n = words.len();
+            let found = self.pos + n <= self.words.len() && words.iter().enumerate()
+                .all(|(i, w)| self.words[self.pos + i].to_lowercase() == *w);
+            if found && best.as_ref().map(|&(_, m)| n > m).unwrap_or(true) {
+                best = Some((verb.clone(), n));
+            }
+        }
+        best.map(|(verb, n)| {
+            self.pos += n;
+            verb
+        })
+    }
+
+    fn statement(&mut self) -> Result<(Object, Object), ParseError> {
+        let subject = self.noun_phrase(true)?;
+        Ok((subject, self.predicate()?))
+    }
+
+    /// Reads what a statement says about its subject, e.g. "is locked" or "killed John".
+    fn predicate(&mut self) -> Result<Object, ParseError> {
+        let word = self.next()?;
+        Ok(match &*word {
+            "is" | "am" | "are" => self.complement()?,
+            "was" | "were" => self.passive()?,
+            "has" | "have" => has(self.noun_phrase(true)?),
+            "does" | "do" => {
+                self.expect("not")?;
+                self.expect("have")?;
+                has_not(self.noun_phrase(true)?)
+            }
+            _ => {
+                self.pos -= 1;
+                let verb = self.verb_form(&self.verbs.all(), Verb::past)
+                    .ok_or(ParseError::UnknownVerb(word))?;
+                Object::DidTo(verb, Box::new(self.noun_phrase(true)?))
+            }
+        })
+    }
+
+    /// Reads what follows "is", e.g. "locked", "on the roof" or "a murderer".
+    fn complement(&mut self) -> Result<Object, ParseError> {
+        let word = self.peek().ok_or(ParseError::UnexpectedEnd)?;
+        Ok(if let Some(adj) = self.adjective(&word) {
+            self.pos += 1;
+            Object::Adj(adj)
+        } else if self.eat("leaning") {
+            self.expect("toward")?;
+            lean_toward(self.noun_phrase(true)?).into()
+        } else if self.eat("called") {
+            let name = *self.words.get(self.pos).ok_or(ParseError::UnexpectedEnd)?;
+            self.pos += 1;
+            called(name)
+        } else if self.eat("a") || self.eat("an") {
+            let noun = self.next()?;
+            if noun == "murderer" {Object::Adj(Adjective::Murderer)} else {of_type(&noun)}
+        } else if word == "the" {
+            self.pos += 1;
+            self.expect("opponent")?;
+            self.expect("of")?;
+            opponent_of(self.noun_phrase(true)?).into()
+        } else {
+            self.placement()?.into()
+        })
+    }
+
+    /// Reads a verb in passive voice, e.g. "killed by Peter".
+    fn passive(&mut self) -> Result<Object, ParseError> {
+        let verb = self.verb_form(&self.verbs.all(), Verb::participle)
+            .ok_or_else(|| ParseError::UnknownVerb(self.peek().unwrap_or_default()))?;
+        self.expect("by")?;
+        Ok(Object::WasBy(verb, Box::new(self.noun_phrase(true)?)))
+    }
+
+    fn question(&mut self) -> Result<Question, ParseError> {
+        let word = self.peek().ok_or(ParseError::UnexpectedEnd)?;
+        let second = self.words.get(self.pos + 1).map(|w| w.to_lowercase());
+        let wh = matches!(&*word, "who" | "whom" | "what");
+        match (&*word, second.as_deref()) {
+            ("where", _) => {
+                self.pos += 1;
+                let word = self.next()?;
+                if !["is", "am", "are", "was", "were"].contains(&&*word) {
+                    return Err(ParseError::Expected("is", word));
+                }
+                Ok(Question::Where(self.noun_phrase(true)?))
+            }
+            (_, Some("does")) | (_, Some("do")) | (_, Some("did")) if wh => {
+                // Ask for the object, e.g. "what does Sheila have",
+                // where the question word fills the missing noun phrase at the end.
+                self.pos += 1;
+                self.gap = Some(Object::Var(Arc::new(word.clone())));
+                let question = self.inverted()?;
+                if self.gap.take().is_some() {return Err(ParseError::UnusedQuestionWord(word))};
+                Ok(question)
+            }
+            _ if wh => {
+                // Ask for the subject, e.g. "who killed John".
+                self.pos += 1;
+                let subject = Object::Var(Arc::new(word));
+                Ok(Question::from_statement(subject, self.predicate()?))
+            }
+            ("is", _) | ("am", _) | ("are", _) | ("was", _) | ("were", _) |
+            ("has", _) | ("have", _) | ("does", _) | ("do", _) | ("did", _) => self.inverted(),
+            _ => {
+                let (obj, prop) = self.statement()?;
+                Ok(Question::from_statement(obj, prop))
+            }
+        }
+    }
+
+    /// Reads a question starting with an auxiliary verb, e.g. "is the door locked".
+    fn inverted(&mut self) -> Result<Question, ParseError> {
+        let aux = self.next()?;
+        if aux == "did" {
+            let Action::Do {subject, verb, object, ..} = self.action()?;
+            return Ok(Question::from_statement(subject, Object::DidTo(verb, Box::new(object))));
+        }
+        let subject = self.noun_phrase(true)?;
+        let prop = match &*aux {
+            "is" | "am" | "are" => self.complement()?,
+            "was" | "were" => self.passive()?,
+            "has" | "have" => has(self.noun_phrase(true)?),
+            "does" | "do" => {
+                let not = self.eat("not");
+                self.expect("have")?;
+                let item = self.noun_phrase(true)?;
+                if not {has_not(item)} else {has(item)}
+            }
+            _ => return Err(ParseError::Expected("is", aux)),
+        };
+        Ok(Question::from_statement(subject, prop))
+    }
+
+    fn action(&mut self) -> Result<Action, ParseError> {
+        let subject = self.noun_phrase(true)?;
+        // Only custom verbs with a subject and an object are read this way.
+        let custom: Vec<Verb> = self.verbs.custom().iter()
+            .filter(|verb| verb.arity() == 2)
+            .cloned()
+            .collect();
+        for &form in &[Verb::base, Verb::present, Verb::past] {
+            if let Some(verb) = self.verb_form(&custom, form) {
+                return Ok(subject.does(verb, self.noun_phrase(true)?));
+            }
+        }
+        let word = self.next()?;
+        let verb = VERBS.iter().find(|&&(_, forms)| forms.contains(&&*word))
+            .ok_or(ParseError::UnknownVerb(word))?.0;
+        Ok(match verb {
+            "carry" => subject.carries(self.noun_phrase(true)?),
+            "climb" => {
+                if self.eat("into") {subject.climbs_into(self.noun_phrase(true)?)}
+                else if self.eat("out") {
+                    self.expect("of")?;
+                    subject.climbs_out_of(self.noun_phrase(true)?)
+                } else {
+                    let object = self.noun_phrase(true)?;
+                    subject.climbs_to(object, self.placement()?)
+                }
+            }
+            "close" => subject.closes(self.noun_phrase(true)?),
+            "discard" | "drop" => subject.puts_down(self.noun_phrase(true)?),
+            "get" | "grab" => subject.picks_up(self.noun_phrase(true)?),
+            "go" | "journey" | "travel" => {
+                self.eat("back");
+                self.expect("to")?;
+                subject.goes_to(self.noun_phrase(true)?)
+            }
+            "give" => {
+                let start = self.pos;
+                let first = self.noun_phrase(true)?;
+                if self.peek().is_none() {
+                    // Read "give the key to her" as giving the key to her.
+                    self.pos = start;
+                    let item = self.noun_phrase(false)?;
+                    self.expect("to")?;
+                    subject.gives_item(self.noun_phrase(true)?, item)
+                } else if self.eat("to") {
+                    subject.gives_item(self.noun_phrase(true)?, first)
+                } else {
+                    subject.gives_item(first, self.noun_phrase(true)?)
+                }
+            }
+            "kill" => subject.kills(self.noun_phrase(true)?),
+            "lean" => {
+                let word = self.next()?;
+                if !["toward", "towards", "against"].contains(&&*word) {
+                    return Err(ParseError::Expected("toward", word));
+                }
+                subject.leans_toward(self.noun_phrase(true)?)
+            }
+            "lock" => subject.locks(self.noun_phrase(true)?),
+            "move" => {
+                if self.eat("back") {
+                    self.expect("to")?;
+                    subject.goes_to(self.noun_phrase(true)?)
+                } else if self.eat("to") {
+                    subject.goes_to(self.noun_phrase(true)?)
+                } else {
+                    let object = self.noun_phrase(true)?;
+                    subject.moves(object, self.placement()?)
+                }
+            }
+            "open" => subject.opens(self.noun_phrase(true)?),
+            "pick" => {
+                self.expect("up")?;
+                subject.picks_up(self.noun_phrase(true)?)
+            }
+            "play" => {
+                let game = self.noun_phrase(true)?;
+                self.expect("against")?;
+                subject.plays_against(game, self.noun_phrase(true)?)
+            }
+            "put" => {
+                self.expect("down")?;
+                subject.puts_down(self.noun_phrase(true)?)
+            }
+            "sleep" => {
+                self.expect("in")?;
+                subject.sleeps_in(self.noun_phrase(true)?)
+            }
+            "stand" => {
+                self.expect("on")?;
+                subject.stands_on(self.noun_phrase(true)?)
+            }
+            "take" => subject.picks_up(self.noun_phrase(true)?),
+            "talk" => {
+                self.expect("to")?;
+                subject.talk_to(self.noun_phrase(true)?)
+            }
+            "unlock" => subject.unlocks(self.noun_phrase(true)?),
+            "wake" => {
+                self.expect("up")?;
+                self.expect("in")?;
+                subject.wakes_up_in(self.noun_phrase(true)?)
+            }
+            "walk" => {
+                self.expect("through")?;
+                subject.walks_through(self.noun_phrase(true)?)
+            }
+            _ => unreachable!(),
+        })
+    }
+}
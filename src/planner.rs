@@ -0,0 +1,126 @@
+use *;
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+/// A goal to achieve in a room.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Goal {
+    /// Properties that must hold.
+    pub require: Vec<(Object, Object)>,
+    /// Properties that must not hold.
+    pub prevent: Vec<(Object, Object)>,
+}
+
+impl Goal {
+    /// Creates a new goal from properties that must hold.
+    pub fn new(require: Vec<(Object, Object)>) -> Goal {
+        Goal {require, prevent: vec![]}
+    }
+
+    /// Returns `true` if the goal is achieved in the room.
+    pub fn achieved(&self, room: &Room) -> bool {self.distance(room) == 0}
+
+    /// Returns the number of criteria that are not satisfied.
+    pub fn distance(&self, room: &Room) -> usize {
+        self.require.iter().filter(|(obj, prop)| !room.holds(obj, prop)).count() +
+        self.prevent.iter().filter(|(obj, prop)| room.holds(obj, prop)).count()
+    }
+}
+
+/// Search strategy used by the planner.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Search {
+    /// Explore shorter plans first.
+    ///
+    /// Finds the shortest plan.
+    BreadthFirst,
+    /// Explore plans ordered by length plus number of unsatisfied criteria.
+    ///
+    /// Usually explores fewer states, but the plan is not guaranteed to be the shortest.
+    /// States reached again by a shorter plan are explored again,
+    /// so a plan is found whenever one exists within the bound.
+    AStar,
+}
+
+/// Stores the reason why no plan was found.
+#[derive(Clone, Debug, PartialEq)]
+pub enum NoPlan {
+    /// All reachable states were explored, so no plan exists of any length.
+    Impossible {
+        /// The number of explored states.
+        states: usize,
+    },
+    /// No plan exists within the bound, but there are unexplored states beyond it.
+    Bound {
+        /// The number of explored states.
+        states: usize,
+    },
+}
+
+struct Node {
+    room: Room,
+    parent: Option<(usize, usize)>,
+    depth: usize,
+}
+
+impl Room {
+    /// Searches for a sequence of actions that achieves a goal.
+    ///
+    /// Each action may be used any number of times.
+    /// Returns indices into `actions` in the order they should be executed.
+    pub fn plan(
+        &self,
+        goal: &Goal,
+        actions: &[Action],
+        max_depth: usize,
+        search: Search
+    ) -> Result<Vec<usize>, NoPlan> {
+        let mut room = self.clone();
+        room.common_sense = None;
+        // The shortest known plan length to each state.
+        let mut depths: HashMap<Vec<Object>, usize> = HashMap::new();
+        depths.insert(room.objects.clone(), 0);
+        let mut nodes = vec![Node {room, parent: None, depth: 0}];
+        // Ties are broken by insertion order, which makes the search breadth first.
+        let mut queue = BinaryHeap::new();
+        queue.push(Reverse((0, 0)));
+        let mut bounded = false;
+        while let Some(Reverse((_, i))) = queue.pop() {
+            // Skip states that were reached again by a shorter plan.
+            if depths[&nodes[i].room.objects] < nodes[i].depth {continue};
+            if goal.achieved(&nodes[i].room) {
+                let mut plan = vec![];
+                let mut i = i;
+                while let Some((parent, action)) = nodes[i].parent {
+                    plan.push(action);
+                    i = parent;
+                }
+                plan.reverse();
+                return Ok(plan);
+            }
+
+            let depth = nodes[i].depth + 1;
+            for (j, action) in actions.iter().enumerate() {
+                let mut next = nodes[i].room.clone();
+                if next.force_action(action).is_err() {continue};
+                if depths.get(&next.objects).map(|&d| d <= depth).unwrap_or(false) {continue};
+                if depth > max_depth {
+                    bounded = true;
+                    break;
+                }
+
+                let priority = match search {
+                    Search::BreadthFirst => depth,
+                    Search::AStar => depth + goal.distance(&next),
+                };
+                depths.insert(next.objects.clone(), depth);
+                nodes.push(Node {room: next, parent: Some((i, j)), depth});
+                queue.push(Reverse((priority, nodes.len() - 1)));
+            }
+        }
+        let states = depths.len();
+        if bounded {Err(NoPlan::Bound {states})}
+        else {Err(NoPlan::Impossible {states})}
+    }
+}
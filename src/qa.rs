@@ -0,0 +1,124 @@
+use *;
+
+/// A question about the room.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Question {
+    /// Asks whether an object has a property, e.g. "Is the door locked?".
+    YesNo(Object, Object),
+    /// Asks for the values of a variable in a pattern, e.g. "Who killed John?".
+    Find(Object, Arc<String>),
+    /// Asks where an object is, e.g. "Where is the ladder?".
+    Where(Object),
+}
+
+/// An answer to a question.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Answer {
+    /// The object has the property.
+    Yes,
+    /// The object has a property that excludes the one asked for.
+    No,
+    /// The room does not tell.
+    Unknown,
+    /// Descriptions of the objects asked for.
+    Objects(Vec<Object>),
+    /// The places of the object asked for.
+    Places(Vec<Placement>),
+}
+
+impl Question {
+    /// Creates a question from a statement, asking for a variable if there is one.
+    pub fn from_statement(obj: Object, prop: Object) -> Question {
+        match first_var(&obj).or_else(|| first_var(&prop)) {
+            Some(name) => Question::Find(Object::And(vec![obj, prop]), name),
+            None => Question::YesNo(obj, prop),
+        }
+    }
+}
+
+/// Returns the name of the first variable in an object.
+fn first_var(obj: &Object) -> Option<Arc<String>> {
+    use Object::*;
+
+    match *obj {
+        Var(ref name) => Some(name.clone()),
+        And(ref list) => list.iter().find_map(first_var),
+        Placement(ref place) => first_var(place.obj_ref()),
+        Role(ref role) => first_var(role.obj_ref()),
+        Has(ref obj) | HasNot(ref obj) | KeyTo(ref obj) |
+        WasBy(_, ref obj) | DidTo(_, ref obj) => first_var(obj),
+        I | You | He | She | It | That | Called(_) | OfType(_) | Adj(_) | Any | Id(_) => None,
+    }
+}
+
+/// Returns `true` if an object has a property that rules out another.
+///
+/// Actions are recorded in the room, so events that are not there did not happen.
+fn excludes(obj: &Object, prop: &Object, adjectives: &Adjectives) -> bool {
+    use Object::*;
+
+    let props: &[Object] = if let And(ref list) = *obj {list} else {std::slice::from_ref(obj)};
+    match *prop {
+        Adj(ref adj) => adjectives.excluded(adj).into_iter().any(|other| obj.matches(&other.into())),
+        Has(ref item) => obj.matches(&HasNot(item.clone())),
+        HasNot(ref item) => obj.matches(&Has(item.clone())),
+        WasBy(_, _) | DidTo(_, _) => true,
+        OfType(_) => props.iter().any(|p| matches!(p, OfType(_))),
+        Called(_) => props.iter().any(|p| matches!(p, Called(_))),
+        Placement(ref place) => match **place {
+            self::Placement::On(_) | self::Placement::In(_) => props.iter().any(|p| {
+                matches!(p, Placement(other)
+                    if matches!(**other, self::Placement::On(_) | self::Placement::In(_)))
+            }),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+impl Room {
+    /// Answers a question from the state of the room.
+    pub fn answer(&self, question: &Question) -> Answer {
+        match *question {
+            Question::YesNo(ref obj, ref prop) => match self.find(obj) {
+                Ok(ind) if self.taxonomy.matches(&self.objects[ind], prop) => Answer::Yes,
+                Ok(ind) if excludes(&self.objects[ind], prop, &self.adjectives) => Answer::No,
+                _ => Answer::Unknown,
+            },
+            Question::Find(ref pattern, ref name) => {
+                let mut res: Vec<Object> = vec![];
+                for bindings in self.query(pattern) {
+                    if let Some(val) = bindings.get(name) {
+                        if !res.contains(val) {res.push(val.clone())};
+                    }
+                }
+                if res.is_empty() {Answer::Unknown} else {Answer::Objects(res)}
+            }
+            Question::Where(ref obj) => {
+                let places = |ind: usize| -> Vec<Placement> {
+                    match self.objects[ind] {
+                        Object::And(ref list) => list.iter().filter_map(|p| {
+                            if let Object::Placement(ref place) = *p {Some((**place).clone())}
+                            else {None}
+                        }).collect(),
+                        _ => vec![],
+                    }
+                };
+                let mut res = vec![];
+                if let Ok(ind) = self.find(obj) {
+                    res = places(ind);
+                    // Items that are carried are where the one carrying them is.
+                    if res.is_empty() {
+                        if let Ok(holder) = self.find(&has(obj.clone())) {res = places(holder)};
+                    }
+                }
+                if res.is_empty() {Answer::Unknown} else {Answer::Places(res)}
+            }
+        }
+    }
+
+    /// Parses and answers a question in English, e.g. "Who killed John?".
+    pub fn ask(&self, text: &str) -> Result<Answer, ParseError> {
+        Ok(self.answer(&self.parse_question(text)?))
+    }
+}
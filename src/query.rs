@@ -0,0 +1,105 @@
+use *;
+
+/// Values of variables found by unifying a pattern with an object.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Bindings {
+    /// The variables and their values, in the order they were bound.
+    pub vars: Vec<(Arc<String>, Object)>,
+}
+
+impl Bindings {
+    /// Creates empty bindings.
+    pub fn new() -> Bindings {
+        Bindings {vars: vec![]}
+    }
+
+    /// Returns the value of a variable.
+    pub fn get(&self, name: &str) -> Option<&Object> {
+        self.vars.iter().find(|(var, _)| ***var == *name).map(|(_, val)| val)
+    }
+
+    /// Replaces bound variables in a pattern with their values.
+    pub fn apply(&self, pattern: &Object) -> Object {
+        pattern.substitute(&|obj| match *obj {
+            Object::Var(ref name) => self.get(name).cloned(),
+            _ => None,
+        })
+    }
+
+    fn bind(&self, name: &Arc<String>, val: &Object) -> Vec<Bindings> {
+        match self.get(name) {
+            Some(old) => if val.matches(old) {vec![self.clone()]} else {vec![]},
+            None => {
+                let mut res = self.clone();
+                res.vars.push((name.clone(), val.clone()));
+                vec![res]
+            }
+        }
+    }
+}
+
+impl Object {
+    /// Unifies the object with a pattern containing variables.
+    ///
+    /// Returns every set of bindings for which the object matches the pattern.
+    pub fn unify(&self, pattern: &Object, bindings: &Bindings) -> Vec<Bindings> {
+        self.unify_in(pattern, bindings, &Taxonomy::new())
+    }
+
+    /// Unifies with a pattern, where types in the pattern also match their sub-types.
+    fn unify_in(&self, pattern: &Object, bindings: &Bindings, taxonomy: &Taxonomy) -> Vec<Bindings> {
+        use Object::*;
+
+        match (self, pattern) {
+            (_, Var(name)) => bindings.bind(name, self),
+            (_, And(patterns)) => {
+                let mut res = vec![bindings.clone()];
+                for p in patterns {
+                    res = res.iter().flat_map(|b| self.unify_in(p, b, taxonomy)).collect();
+                }
+                res
+            }
+            (And(objs), _) => {
+                objs.iter().flat_map(|obj| obj.unify_in(pattern, bindings, taxonomy)).collect()
+            }
+            (Placement(a), Placement(b)) => {
+                use self::Placement::*;
+
+                match (&**a, &**b) {
+                    (On(a), On(b)) | (LeanToward(a), LeanToward(b)) |
+                    (In(a), In(b)) | (OutOf(a), OutOf(b)) => a.unify_in(b, bindings, taxonomy),
+                    _ => vec![],
+                }
+            }
+            (Role(a), Role(b)) => a.obj_ref().unify_in(b.obj_ref(), bindings, taxonomy),
+            (Has(a), Has(b)) | (HasNot(a), HasNot(b)) | (KeyTo(a), KeyTo(b)) => {
+                a.unify_in(b, bindings, taxonomy)
+            }
+            (WasBy(va, a), WasBy(vb, b)) | (DidTo(va, a), DidTo(vb, b)) => {
+                if va == vb {a.unify_in(b, bindings, taxonomy)} else {vec![]}
+            }
+            _ => if taxonomy.matches(self, pattern) {vec![bindings.clone()]} else {vec![]},
+        }
+    }
+}
+
+impl Room {
+    /// Finds every set of bindings for which some object in the room matches a pattern.
+    ///
+    /// Variables bound to a whole object get the shortest description of it,
+    /// e.g. "who killed John?" is `And(vec![var("who"), killed(called("John"))])`.
+    pub fn query(&self, pattern: &Object) -> Vec<Bindings> {
+        let mut res: Vec<Bindings> = vec![];
+        for (ind, obj) in self.objects.iter().enumerate() {
+            for mut bindings in obj.unify_in(pattern, &Bindings::new(), &self.taxonomy) {
+                for (_, val) in &mut bindings.vars {
+                    if val == obj {
+                        if let Some(description) = self.refer(ind) {*val = description};
+                    }
+                }
+                if !res.contains(&bindings) {res.push(bindings)};
+            }
+        }
+        res
+    }
+}
@@ -0,0 +1,47 @@
+use *;
+
+impl Room {
+    /// Returns the shortest description that identifies an object uniquely,
+    /// e.g. `And(vec![of_type("door"), Locked.into()])`.
+    ///
+    /// Uses the name, pronoun or type of the object when there is one,
+    /// adding as few of its other properties as possible.
+    /// Returns `None` if no description tells the object apart from the others.
+    pub fn refer(&self, ind: usize) -> Option<Object> {
+        use Object::*;
+
+        let props: &[Object] = match self.objects[ind] {
+            And(ref list) => list,
+            ref obj => std::slice::from_ref(obj),
+        };
+        let head = props.iter().find(|p| matches!(p, Called(_)))
+            .or_else(|| props.iter().find(|p| matches!(p, I | You | He | She | It | That)))
+            .or_else(|| props.iter().find(|p| matches!(p, OfType(_) | KeyTo(_))));
+        let others: Vec<&Object> = props.iter()
+            .filter(|&p| Some(p) != head && !matches!(p, Var(_) | Any | Id(_)))
+            .collect();
+        let pattern = |subset: &[usize]| {
+            let mut list: Vec<Object> = head.into_iter().cloned().collect();
+            list.extend(subset.iter().map(|&i| others[i].clone()));
+            if list.len() == 1 {list.pop().unwrap()} else {And(list)}
+        };
+        let unique = |subset: &[usize]| self.find(&pattern(subset)) == Ok(ind);
+
+        let all: Vec<usize> = (0..others.len()).collect();
+        if !unique(&all) {return None};
+        let start = if head.is_some() {0} else {1};
+        for size in start..others.len() {
+            let mut subset: Vec<usize> = (0..size).collect();
+            loop {
+                if unique(&subset) {return Some(pattern(&subset))};
+                // Advance to the next combination in lexicographic order.
+                let mut i = size;
+                while i > 0 && subset[i - 1] == others.len() - size + i - 1 {i -= 1}
+                if i == 0 {break};
+                subset[i - 1] += 1;
+                for j in i..size {subset[j] = subset[j - 1] + 1}
+            }
+        }
+        Some(pattern(&all))
+    }
+}
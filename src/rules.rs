@@ -0,0 +1,74 @@
+use *;
+
+/// A rule in the background theory of a room.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Rule {
+    /// Objects matching the pattern get the property.
+    Implies(Object, Object),
+    /// Objects matching the pattern can not be the subject of an action.
+    CannotAct(Object),
+    /// Objects matching the pattern can only be the subject of the verbs.
+    CanOnly(Object, Vec<Verb>),
+    /// Objects matching the pattern do not respond when they are the object of the verb.
+    Unresponsive(Verb, Object),
+}
+
+impl Rule {
+    /// Returns the standard background theory.
+    pub fn standard() -> Vec<Rule> {
+        use Adjective::*;
+
+        vec![
+            Rule::Implies(Locked.into(), Closed.into()),
+            Rule::Implies(killed(Object::Any), Murderer.into()),
+            Rule::CannotAct(Dead.into()),
+            Rule::CannotAct(Unconscious.into()),
+            Rule::CanOnly(Asleep.into(), vec![Verb::WakeUpIn]),
+            Rule::Unresponsive(Verb::Talk, Dead.into()),
+            Rule::Unresponsive(Verb::Talk, Asleep.into()),
+            Rule::Unresponsive(Verb::Talk, Unconscious.into()),
+        ]
+    }
+}
+
+impl Room {
+    /// Applies rules that derive properties until nothing changes.
+    ///
+    /// Stops after one round per rule plus one, in case rules undo each other.
+    /// Returns the number of rounds.
+    pub fn apply_rules(&mut self) -> usize {
+        let max_rounds = self.rules.len() + 1;
+        for round in 1..max_rounds + 1 {
+            let mut changed = false;
+            for rule in &self.rules {
+                if let Rule::Implies(ref premise, ref conclusion) = *rule {
+                    for obj in &mut self.objects {
+                        if self.taxonomy.matches(obj, premise) && !self.taxonomy.matches(obj, conclusion) {
+                            self.adjectives.decorate(obj, conclusion.clone());
+                            changed = true;
+                        }
+                    }
+                }
+            }
+            if !changed {return round};
+        }
+        max_rounds
+    }
+
+    /// Returns the pattern of a rule that prevents an object from acting with the verb.
+    pub fn cannot_act(&self, ind: usize, verb: &Verb) -> Option<&Object> {
+        self.rules.iter().filter_map(|rule| match *rule {
+            Rule::CannotAct(ref pattern) => Some(pattern),
+            Rule::CanOnly(ref pattern, ref verbs) if !verbs.contains(verb) => Some(pattern),
+            _ => None,
+        }).find(|pattern| self.taxonomy.matches(&self.objects[ind], pattern))
+    }
+
+    /// Returns the pattern of a rule that makes an object unresponsive to the verb.
+    pub fn unresponsive(&self, ind: usize, verb: &Verb) -> Option<&Object> {
+        self.rules.iter().filter_map(|rule| match *rule {
+            Rule::Unresponsive(ref v, ref pattern) if v == verb => Some(pattern),
+            _ => None,
+        }).find(|pattern| self.taxonomy.matches(&self.objects[ind], pattern))
+    }
+}
@@ -0,0 +1,164 @@
+use *;
+use english::statement;
+
+use std::fmt;
+
+/// A step in a scenario.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Step {
+    /// Adds an object to the room.
+    Object(Object),
+    /// Adds a property to an object in the room.
+    Fact(Object, Object),
+    /// Executes an action that is expected to succeed.
+    Succeeds(Action),
+    /// Executes an action that is expected to fail.
+    Fails(Action),
+    /// Expects an object to have a property.
+    Holds(Object, Object),
+    /// Expects an object to not have a property.
+    HoldsNot(Object, Object),
+}
+
+/// Describes why a scenario could not be parsed.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ScenarioError {
+    /// The line does not start with a known keyword.
+    UnknownKeyword(usize, String),
+    /// The text after the keyword could not be parsed.
+    Parse(usize, ParseError),
+}
+
+/// Describes a step in a scenario that did not turn out as expected.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Failure {
+    /// An object that a fact refers to could not be identified.
+    Fact(usize, ActionError),
+    /// An action was expected to succeed, but failed.
+    ActionFailed(usize, ActionError),
+    /// An action was expected to fail, but succeeded.
+    ActionSucceeded(usize),
+    /// An object was expected to have a property.
+    Missing(usize, Object, Object),
+    /// An object was expected to not have a property.
+    Present(usize, Object, Object),
+}
+
+/// A scenario written in plain text.
+///
+/// Each line starts with a keyword followed by `:` and English text:
+///
+/// - `object: <noun phrase>` adds an object to the room
+/// - `fact: <statement>` adds a property to an object
+/// - `do: <sentence>` executes an action that should succeed
+/// - `fail: <sentence>` executes an action that should fail
+/// - `expect: <statement>` checks that a property holds
+/// - `expect not: <statement>` checks that a property does not hold
+///
+/// Empty lines and lines starting with `#` are ignored.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Scenario {
+    /// The steps with their line numbers.
+    pub steps: Vec<(usize, Step)>,
+}
+
+impl Scenario {
+    /// Parses a scenario.
+    pub fn parse(text: &str) -> Result<Scenario, ScenarioError> {
+        let mut steps = vec![];
+        for (i, line) in text.lines().enumerate() {
+            let n = i + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {continue};
+            let (keyword, rest) = match line.find(':') {
+                Some(pos) => (line[..pos].trim(), &line[pos + 1..]),
+                None => return Err(ScenarioError::UnknownKeyword(n, line.into())),
+            };
+            let step = match keyword {
+                "object" => parse_object(rest).map(Step::Object),
+                "fact" => parse_statement(rest).map(|(obj, prop)| Step::Fact(obj, prop)),
+                "do" => parse_action(rest).map(Step::Succeeds),
+                "fail" => parse_action(rest).map(Step::Fails),
+                "expect" => parse_statement(rest).map(|(obj, prop)| Step::Holds(obj, prop)),
+                "expect not" => parse_statement(rest).map(|(obj, prop)| Step::HoldsNot(obj, prop)),
+                _ => return Err(ScenarioError::UnknownKeyword(n, keyword.into())),
+            };
+            steps.push((n, step.map_err(|err| ScenarioError::Parse(n, err))?));
+        }
+        Ok(Scenario {steps})
+    }
+
+    /// Runs the scenario in an empty room.
+    ///
+    /// Continues after failed steps and returns all failures.
+    pub fn run(&self) -> Vec<Failure> {
+        self.run_with(&|action| action.clone())
+    }
+
+    /// Runs the scenario, changing each action before it is executed.
+    pub fn run_with(&self, f: &dyn Fn(&Action) -> Action) -> Vec<Failure> {
+        let mut room = Room::new(vec![]);
+        let mut failures = vec![];
+        for &(n, ref step) in &self.steps {
+            match *step {
+                Step::Object(ref obj) => room.objects.push(obj.clone()),
+                Step::Fact(ref obj, ref prop) => {
+                    match room.identify(obj) {
+                        Ok(ind) => room.adjectives.decorate(&mut room.objects[ind], prop.clone()),
+                        Err(err) => failures.push(Failure::Fact(n, err)),
+                    }
+                }
+                Step::Succeeds(ref action) => {
+                    if let Err(err) = room.action(&f(action)) {
+                        failures.push(Failure::ActionFailed(n, err));
+                    }
+                }
+                Step::Fails(ref action) => {
+                    if room.action(&f(action)).is_ok() {
+                        failures.push(Failure::ActionSucceeded(n));
+                    }
+                }
+                Step::Holds(ref obj, ref prop) => {
+                    if !room.holds(obj, prop) {
+                        failures.push(Failure::Missing(n, obj.clone(), prop.clone()));
+                    }
+                }
+                Step::HoldsNot(ref obj, ref prop) => {
+                    if room.holds(obj, prop) {
+                        failures.push(Failure::Present(n, obj.clone(), prop.clone()));
+                    }
+                }
+            }
+        }
+        failures
+    }
+}
+
+impl fmt::Display for ScenarioError {
+    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ScenarioError::UnknownKeyword(n, ref keyword) => {
+                write!(w, "line {}: unknown keyword `{}`", n, keyword)
+            }
+            ScenarioError::Parse(n, ref err) => write!(w, "line {}: {}", n, err),
+        }
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Failure::Fact(n, ref err) => write!(w, "line {}: {}", n, err),
+            Failure::ActionFailed(n, ref err) => {
+                write!(w, "line {}: expected success, but failed: {}", n, err)
+            }
+            Failure::ActionSucceeded(n) => write!(w, "line {}: expected failure, but succeeded", n),
+            Failure::Missing(n, ref obj, ref prop) => {
+                write!(w, "line {}: expected that {}", n, statement(obj, prop))
+            }
+            Failure::Present(n, ref obj, ref prop) => {
+                write!(w, "line {}: expected not that {}", n, statement(obj, prop))
+            }
+        }
+    }
+}
@@ -0,0 +1,426 @@
+use *;
+
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// Describes why action schemas could not be loaded or instantiated.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SchemaError {
+    /// The file could not be read.
+    Io(String),
+    /// The line has invalid syntax.
+    Syntax(usize, String),
+    /// The line refers to an unknown verb, adjective or function.
+    Unknown(usize, String),
+    /// The action starting at the line has no verb.
+    MissingVerb(usize),
+    /// The verb declared at the line has no English forms.
+    MissingForms(usize),
+    /// The verb declared at the line has the name of another verb.
+    DuplicateVerb(usize, String),
+    /// The verb takes another number of participants than the action has parameters.
+    VerbArity {
+        /// The line of the verb.
+        line: usize,
+        /// The arity of the verb.
+        expected: usize,
+        /// The number of parameters.
+        found: usize,
+    },
+    /// The action was instantiated with the wrong number of arguments.
+    Arity {
+        /// The number of parameters.
+        expected: usize,
+        /// The number of arguments.
+        found: usize,
+    },
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        use SchemaError::*;
+
+        match *self {
+            Io(ref err) => write!(w, "could not read the file: {}", err),
+            Syntax(n, ref text) => write!(w, "line {}: could not understand `{}`", n, text),
+            Unknown(n, ref name) => write!(w, "line {}: unknown name `{}`", n, name),
+            MissingVerb(n) => write!(w, "line {}: the action has no `verb:` line", n),
+            MissingForms(n) => write!(w, "line {}: the verb has no `forms:` line", n),
+            DuplicateVerb(n, ref name) => write!(w, "line {}: the verb `{}` already exists", n, name),
+            VerbArity {line, expected, found} => {
+                write!(w, "line {}: the verb takes {} participants, but the action has {} parameters",
+                       line, expected, found)
+            }
+            Arity {expected, found} => {
+                write!(w, "expected {} arguments, found {}", expected, found)
+            }
+        }
+    }
+}
+
+/// An action definition with parameters.
+///
+/// Schemas are written in plain text, for example:
+///
+/// ```text
+/// action unlocks(subject, object)
+/// verb: Unlock
+/// decorate: object, Unlocked
+/// remove: object, Locked
+/// require: subject, has(key_to(object))
+/// distinct: subject, object
+/// end
+/// ```
+///
+/// The first parameter is the subject and the second is the object,
+/// unless set by `subject:` and `object:` lines.
+/// Objects use the same names as the functions in this library,
+/// e.g. `has(key_to(object))`, `on(of_type("roof"))` or `and(He, called("Peter"))`.
+///
+/// New verbs are declared before the actions using them,
+/// with the base form, present tense, past tense and past participle:
+///
+/// ```text
+/// verb KnockOn
+/// arity: 2
+/// forms: knock on, knocks on, knocked on, knocked on
+/// end
+/// ```
+///
+/// The arity is 2 unless set, and `inverse:` names the verb that undoes it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ActionSchema {
+    /// The name of the action.
+    pub name: Arc<String>,
+    /// The parameters.
+    pub params: Vec<Arc<String>>,
+    /// The action with parameters as variables.
+    pub action: Action,
+}
+
+impl ActionSchema {
+    /// Creates an action by substituting parameters with arguments.
+    pub fn instantiate(&self, args: &[Object]) -> Result<Action, SchemaError> {
+        if args.len() != self.params.len() {
+            return Err(SchemaError::Arity {expected: self.params.len(), found: args.len()});
+        }
+        Ok(self.action.substitute(&|obj| {
+            if let Object::Var(ref name) = *obj {
+                self.params.iter().position(|p| p == name).map(|i| args[i].clone())
+            } else {
+                None
+            }
+        }))
+    }
+
+    /// Loads action schemas from a file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Vec<ActionSchema>, SchemaError> {
+        ActionSchema::load_with(path, &mut VerbRegistry::new())
+    }
+
+    /// Loads action schemas from a file, registering the verbs it declares.
+    pub fn load_with<P: AsRef<Path>>(
+        path: P,
+        verbs: &mut VerbRegistry
+    ) -> Result<Vec<ActionSchema>, SchemaError> {
+        let text = fs::read_to_string(path).map_err(|err| SchemaError::Io(format!("{}", err)))?;
+        ActionSchema::parse_with(&text, verbs)
+    }
+
+    /// Parses action schemas using built-in verbs and the verbs declared in the text.
+    pub fn parse(text: &str) -> Result<Vec<ActionSchema>, SchemaError> {
+        ActionSchema::parse_with(text, &mut VerbRegistry::new())
+    }
+
+    /// Parses action schemas using built-in and custom verbs.
+    ///
+    /// Verbs declared in the text are registered, so rooms using the registry can read them.
+    pub fn parse_with(text: &str, verbs: &mut VerbRegistry) -> Result<Vec<ActionSchema>, SchemaError> {
+        let mut res = vec![];
+        let mut current: Option<(usize, ActionSchema, bool)> = None;
+        let mut declared: Option<(usize, VerbInfo, bool)> = None;
+        for (i, line) in text.lines().enumerate() {
+            let n = i + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {continue};
+
+            if let Some((start, ref mut info, ref mut has_forms)) = declared {
+                if line == "end" {
+                    if !*has_forms {return Err(SchemaError::MissingForms(start))};
+                    verbs.register(info.clone());
+                    declared = None;
+                    continue;
+                }
+                let (key, rest) = match line.find(':') {
+                    Some(pos) => (line[..pos].trim(), line[pos + 1..].trim()),
+                    None => return Err(SchemaError::Syntax(n, line.into())),
+                };
+                match key {
+                    "arity" => {
+                        info.arity = rest.parse().map_err(|_| SchemaError::Syntax(n, line.into()))?;
+                    }
+                    "inverse" => {
+                        let inverse = verbs.from_name(rest)
+                            .ok_or_else(|| SchemaError::Unknown(n, rest.into()))?;
+                        info.inverse = Some(inverse);
+                    }
+                    "forms" => {
+                        let forms: Vec<String> = rest.split(',').map(|f| f.trim().into()).collect();
+                        if forms.len() != 4 || forms.iter().any(|f| f.is_empty()) {
+                            return Err(SchemaError::Syntax(n, line.into()));
+                        }
+                        info.base = forms[0].clone();
+                        info.present = forms[1].clone();
+                        info.past = forms[2].clone();
+                        info.participle = forms[3].clone();
+                        *has_forms = true;
+                    }
+                    _ => return Err(SchemaError::Unknown(n, key.into())),
+                }
+                continue;
+            }
+
+            if let Some(name) = line.strip_prefix("verb ") {
+                if current.is_some() {return Err(SchemaError::Syntax(n, line.into()))};
+                let name = name.trim();
+                if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                    return Err(SchemaError::Syntax(n, line.into()));
+                }
+                if verbs.from_name(name).is_some() {
+                    return Err(SchemaError::DuplicateVerb(n, name.into()));
+                }
+                declared = Some((n, VerbInfo {
+                    name: name.into(), arity: 2, inverse: None,
+                    base: String::new(), present: String::new(),
+                    past: String::new(), participle: String::new(),
+                }, false));
+                continue;
+            }
+
+            if line == "end" {
+                match current.take() {
+                    Some((_, schema, true)) => res.push(schema),
+                    Some((start, _, false)) => return Err(SchemaError::MissingVerb(start)),
+                    None => return Err(SchemaError::Syntax(n, line.into())),
+                }
+                continue;
+            }
+
+            if let Some(header) = line.strip_prefix("action ") {
+                if current.is_some() {return Err(SchemaError::Syntax(n, line.into()))};
+                let header = header.trim();
+                let (name, params) = match (header.find('('), header.ends_with(')')) {
+                    (Some(pos), true) => (&header[..pos], &header[pos + 1..header.len() - 1]),
+                    _ => return Err(SchemaError::Syntax(n, line.into())),
+                };
+                let params: Vec<Arc<String>> = params.split(',')
+                    .map(|p| p.trim())
+                    .filter(|p| !p.is_empty())
+                    .map(|p| Arc::new(p.into()))
+                    .collect();
+                let subject = params.first().map(|p| Object::Var(p.clone())).unwrap_or(Object::I);
+                let object = params.get(1).map(|p| Object::Var(p.clone())).unwrap_or(Object::I);
+                current = Some((n, ActionSchema {
+                    name: Arc::new(name.trim().into()),
+                    params,
+                    action: Action::Do {
+                        subject, verb: Verb::Talk, object,
+                        decorate: vec![],
+                        remove: vec![],
+                        remove_placement: vec![],
+                        prevent: vec![],
+                        require: vec![],
+                        distinct: vec![],
+                    },
+                }, false));
+                continue;
+            }
+
+            let (_, schema, has_verb) = match current.as_mut() {
+                Some(x) => x,
+                None => return Err(SchemaError::Syntax(n, line.into())),
+            };
+            let (key, rest) = match line.find(':') {
+                Some(pos) => (line[..pos].trim(), line[pos + 1..].trim()),
+                None => return Err(SchemaError::Syntax(n, line.into())),
+            };
+            let Action::Do {
+                ref mut subject, ref mut verb, ref mut object, ref mut decorate, ref mut remove,
+                ref mut remove_placement, ref mut prevent, ref mut require, ref mut distinct,
+            } = schema.action;
+            if key == "verb" {
+                *verb = verbs.from_name(rest).ok_or_else(|| SchemaError::Unknown(n, rest.into()))?;
+                let (expected, found) = (verb.arity(), schema.params.len());
+                if expected != found {
+                    return Err(SchemaError::VerbArity {line: n, expected, found});
+                }
+                *has_verb = true;
+                continue;
+            }
+            let terms = Terms::new(rest, n, &schema.params, verbs).list()?;
+            let pair = || match terms.len() {
+                2 => Ok((terms[0].clone(), terms[1].clone())),
+                _ => Err(SchemaError::Syntax(n, line.into())),
+            };
+            let single = || match terms.len() {
+                1 => Ok(terms[0].clone()),
+                _ => Err(SchemaError::Syntax(n, line.into())),
+            };
+            match key {
+                "subject" => *subject = single()?,
+                "object" => *object = single()?,
+                "decorate" => decorate.push(pair()?),
+                "remove" => remove.push(pair()?),
+                "remove placement" => remove_placement.extend(terms.iter().cloned()),
+                "prevent" => prevent.push(pair()?),
+                "require" => require.push(pair()?),
+                "distinct" => distinct.extend(terms.iter().cloned()),
+                _ => return Err(SchemaError::Unknown(n, key.into())),
+            }
+        }
+        match (current, declared) {
+            (Some((start, _, _)), _) | (_, Some((start, _, _))) => {
+                Err(SchemaError::Syntax(start, "missing `end`".into()))
+            }
+            (None, None) => Ok(res),
+        }
+    }
+}
+
+/// Parses objects written with function names of this library.
+struct Terms<'a> {
+    text: &'a str,
+    pos: usize,
+    line: usize,
+    params: &'a [Arc<String>],
+    verbs: &'a VerbRegistry,
+}
+
+impl<'a> Terms<'a> {
+    fn new(
+        text: &'a str,
+        line: usize,
+        params: &'a [Arc<String>],
+        verbs: &'a VerbRegistry
+    ) -> Terms<'a> {
+        Terms {text, pos: 0, line, params, verbs}
+    }
+
+    fn syntax(&self) -> SchemaError {
+        SchemaError::Syntax(self.line, self.text.into())
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.text[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        if self.text[self.pos..].starts_with(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), SchemaError> {
+        if self.eat(c) {Ok(())} else {Err(self.syntax())}
+    }
+
+    fn list(&mut self) -> Result<Vec<Object>, SchemaError> {
+        let mut res = vec![self.term()?];
+        while self.eat(',') {res.push(self.term()?)}
+        self.skip_whitespace();
+        if self.pos < self.text.len() {return Err(self.syntax())};
+        Ok(res)
+    }
+
+    fn ident(&mut self) -> Result<&'a str, SchemaError> {
+        self.skip_whitespace();
+        let rest = &self.text[self.pos..];
+        let len = rest.find(|c: char| !c.is_alphanumeric() && c != '_').unwrap_or(rest.len());
+        if len == 0 {return Err(self.syntax())};
+        self.pos += len;
+        Ok(&rest[..len])
+    }
+
+    fn string(&mut self) -> Result<&'a str, SchemaError> {
+        self.expect('"')?;
+        let rest = &self.text[self.pos..];
+        let len = rest.find('"').ok_or_else(|| self.syntax())?;
+        self.pos += len + 1;
+        Ok(&rest[..len])
+    }
+
+    fn term(&mut self) -> Result<Object, SchemaError> {
+        use Object::*;
+
+        let name = self.ident()?;
+        if let Some(p) = self.params.iter().find(|p| ***p == *name) {
+            return Ok(Var(p.clone()));
+        }
+        let obj = match name {
+            "I" => I,
+            "You" => You,
+            "He" => He,
+            "She" => She,
+            "It" => It,
+            "That" => That,
+            "Any" => Any,
+            "Dead" => Adjective::Dead.into(),
+            "Murderer" => Adjective::Murderer.into(),
+            "Open" => Adjective::Open.into(),
+            "Closed" => Adjective::Closed.into(),
+            "Locked" => Adjective::Locked.into(),
+            "Unlocked" => Adjective::Unlocked.into(),
+            "Asleep" => Adjective::Asleep.into(),
+            "Awake" => Adjective::Awake.into(),
+            "Unconscious" => Adjective::Unconscious.into(),
+            "Alive" => Adjective::Alive.into(),
+            _ => {
+                if !self.eat('(') {return Err(SchemaError::Unknown(self.line, name.into()))};
+                let obj = match name {
+                    "called" => called(self.string()?),
+                    "of_type" => of_type(self.string()?),
+                    "var" => var(self.string()?),
+                    "adj" => adj(self.string()?).into(),
+                    "was_by" | "did_to" => {
+                        let verb_name = self.ident()?;
+                        let verb = self.verbs.from_name(verb_name)
+                            .ok_or_else(|| SchemaError::Unknown(self.line, verb_name.into()))?;
+                        self.expect(',')?;
+                        let obj = Box::new(self.term()?);
+                        if name == "was_by" {WasBy(verb, obj)} else {DidTo(verb, obj)}
+                    }
+                    "and" => {
+                        let mut list = vec![self.term()?];
+                        while self.eat(',') {list.push(self.term()?)}
+                        And(list)
+                    }
+                    _ => {
+                        let arg = self.term()?;
+                        match name {
+                            "has" => has(arg),
+                            "has_not" => has_not(arg),
+                            "key_to" => key_to(arg),
+                            "killed_by" => killed_by(arg),
+                            "killed" => killed(arg),
+                            "on" => on(arg).into(),
+                            "lean_toward" => lean_toward(arg).into(),
+                            "in" | "in_" => in_(arg).into(),
+                            "out_of" => out_of(arg).into(),
+                            "opponent_of" => opponent_of(arg).into(),
+                            _ => return Err(SchemaError::Unknown(self.line, name.into())),
+                        }
+                    }
+                };
+                self.expect(')')?;
+                obj
+            }
+        };
+        Ok(obj)
+    }
+}
@@ -0,0 +1,91 @@
+use *;
+
+use std::fs;
+use std::path::Path;
+
+/// Describes why a taxonomy could not be loaded.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TaxonomyError {
+    /// The file could not be read.
+    Io(String),
+    /// The line is not of the form `a <type> is a <type>`.
+    Syntax(usize, String),
+}
+
+/// Stores sub-type relations between types, e.g. that a bed is a piece of furniture.
+///
+/// Rooms use their taxonomy when finding objects,
+/// such that `of_type("piece of furniture")` matches a bed.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Taxonomy {
+    /// Pairs of a type and one of its super-types.
+    pub relations: Vec<(Arc<String>, Arc<String>)>,
+}
+
+impl Taxonomy {
+    /// Creates an empty taxonomy, where types only match themselves.
+    pub fn new() -> Taxonomy {
+        Taxonomy {relations: vec![]}
+    }
+
+    /// Declares that objects of one type are also of another type.
+    pub fn is_a(&mut self, sub: &str, sup: &str) {
+        let rel = (Arc::new(sub.into()), Arc::new(sup.into()));
+        if !self.relations.contains(&rel) {self.relations.push(rel)};
+    }
+
+    /// Returns `true` if a type is the same as or a sub-type of another.
+    pub fn subtype_of(&self, sub: &str, sup: &str) -> bool {
+        if sub == sup {return true};
+        let mut visited: Vec<&str> = vec![sub];
+        let mut i = 0;
+        while i < visited.len() {
+            let ty = visited[i];
+            for (a, b) in &self.relations {
+                if **a != ty || visited.contains(&&***b) {continue};
+                if **b == sup {return true};
+                visited.push(b);
+            }
+            i += 1;
+        }
+        false
+    }
+
+    /// Returns `true` if an object matches a pattern,
+    /// where types in the pattern also match their sub-types.
+    pub fn matches(&self, obj: &Object, pattern: &Object) -> bool {
+        if self.relations.is_empty() {return obj.matches(pattern)};
+        obj.matches_by(pattern, &|sub, sup| self.subtype_of(sub, sup))
+    }
+
+    /// Parses sub-type relations, one per line, e.g. `a bed is a piece of furniture`.
+    ///
+    /// Empty lines and lines starting with `#` are ignored.
+    pub fn parse(text: &str) -> Result<Taxonomy, TaxonomyError> {
+        fn noun(text: &str) -> &str {
+            let text = text.trim();
+            text.strip_prefix("a ").or_else(|| text.strip_prefix("an ")).unwrap_or(text).trim()
+        }
+
+        let mut res = Taxonomy::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {continue};
+            let (sub, sup) = match line.find(" is ") {
+                Some(pos) => (noun(&line[..pos]), noun(&line[pos + 4..])),
+                None => return Err(TaxonomyError::Syntax(i + 1, line.into())),
+            };
+            if sub.is_empty() || sup.is_empty() {
+                return Err(TaxonomyError::Syntax(i + 1, line.into()));
+            }
+            res.is_a(sub, sup);
+        }
+        Ok(res)
+    }
+
+    /// Loads sub-type relations from a file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Taxonomy, TaxonomyError> {
+        let text = fs::read_to_string(path).map_err(|err| TaxonomyError::Io(format!("{}", err)))?;
+        Taxonomy::parse(&text)
+    }
+}
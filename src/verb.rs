@@ -1,10 +1,15 @@
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::mem;
+use std::sync::Arc;
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Clone)]
 pub enum Verb {
     Carry,
     Climb,
     Close,
     Give,
+    GoTo,
     Kill,
     LeanToward,
     Lock,
@@ -19,4 +24,187 @@ pub enum Verb {
     WakeUpIn,
     WalkThrough,
     Unlock,
+    /// A verb defined by the user.
+    ///
+    /// Custom verbs are equal when they have the same name.
+    Custom(Arc<VerbInfo>),
+}
+
+impl PartialEq for Verb {
+    fn eq(&self, other: &Verb) -> bool {
+        match (self, other) {
+            (Verb::Custom(a), Verb::Custom(b)) => a.name == b.name,
+            (a, b) => mem::discriminant(a) == mem::discriminant(b),
+        }
+    }
+}
+
+impl Eq for Verb {}
+
+impl Hash for Verb {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        mem::discriminant(self).hash(state);
+        if let Verb::Custom(ref info) = *self {info.name.hash(state)};
+    }
+}
+
+impl fmt::Debug for Verb {
+    fn fmt(&self, w: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Verb::Custom(ref info) => write!(w, "Custom({:?})", info.name),
+            ref verb => w.write_str(verb.name()),
+        }
+    }
+}
+
+/// Stores metadata about a verb.
+///
+/// Custom verbs are registered with `VerbRegistry::register`,
+/// or declared in action schemas with a `verb` block.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VerbInfo {
+    /// The name, e.g. "PickUp".
+    pub name: String,
+    /// The number of participants, including the subject.
+    ///
+    /// Action schemas using the verb must have this many parameters.
+    pub arity: usize,
+    /// The verb that undoes this verb, e.g. `Close` for `Open`.
+    pub inverse: Option<Verb>,
+    /// The base form in English, e.g. "pick up".
+    pub base: String,
+    /// The third person present tense in English, e.g. "picks up".
+    pub present: String,
+    /// The past tense in English, e.g. "picked up".
+    pub past: String,
+    /// The past participle in English, e.g. "given".
+    pub participle: String,
+}
+
+/// Stores custom verbs, so they can be found by name and read in sentences.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct VerbRegistry {
+    custom: Vec<Verb>,
+}
+
+impl VerbRegistry {
+    /// Creates a registry with the built-in verbs only.
+    pub fn new() -> VerbRegistry {
+        VerbRegistry {custom: vec![]}
+    }
+
+    /// Registers a custom verb.
+    ///
+    /// Returns the existing verb if the name is already taken.
+    pub fn register(&mut self, info: VerbInfo) -> Verb {
+        if let Some(verb) = self.from_name(&info.name) {return verb};
+        let verb = Verb::Custom(Arc::new(info));
+        self.custom.push(verb.clone());
+        verb
+    }
+
+    /// Returns the custom verbs, in the order they were registered.
+    pub fn custom(&self) -> &[Verb] {&self.custom}
+
+    /// Returns built-in and custom verbs.
+    pub fn all(&self) -> Vec<Verb> {
+        Verb::BUILTIN.iter().chain(&self.custom).cloned().collect()
+    }
+
+    /// Finds a built-in or custom verb by its name, e.g. "PickUp".
+    pub fn from_name(&self, name: &str) -> Option<Verb> {
+        self.all().into_iter().find(|verb| verb.name() == name)
+    }
+
+    /// Returns the verb that undoes a verb.
+    ///
+    /// Custom verbs are inverses of each other when either declares it.
+    pub fn inverse(&self, verb: &Verb) -> Option<Verb> {
+        verb.inverse().or_else(|| {
+            self.all().into_iter().find(|other| other.inverse().as_ref() == Some(verb))
+        })
+    }
+}
+
+impl Verb {
+    /// All built-in verbs.
+    pub const BUILTIN: &'static [Verb] = &[
+        Verb::Carry, Verb::Climb, Verb::Close, Verb::Give, Verb::GoTo, Verb::Kill, Verb::LeanToward,
+        Verb::Lock, Verb::Move, Verb::Open, Verb::PickUp, Verb::Play, Verb::PutDown,
+        Verb::SleepIn, Verb::StandOn, Verb::Talk, Verb::WakeUpIn, Verb::WalkThrough,
+        Verb::Unlock,
+    ];
+
+    /// Finds a built-in verb by its name, e.g. "PickUp".
+    ///
+    /// Use `VerbRegistry::from_name` to find custom verbs.
+    pub fn from_name(name: &str) -> Option<Verb> {
+        Verb::BUILTIN.iter().find(|verb| verb.name() == name).cloned()
+    }
+
+    /// Returns the name, arity, inverse and English forms.
+    fn parts(&self) -> (&str, usize, Option<Verb>, &str, &str, &str, &str) {
+        use Verb::*;
+
+        match *self {
+            Carry => ("Carry", 2, None, "carry", "carries", "carried", "carried"),
+            Climb => ("Climb", 3, None, "climb", "climbs", "climbed", "climbed"),
+            Close => ("Close", 2, Some(Open), "close", "closes", "closed", "closed"),
+            Give => ("Give", 3, None, "give", "gives", "gave", "given"),
+            GoTo => ("GoTo", 2, None, "go to", "goes to", "went to", "gone to"),
+            Kill => ("Kill", 2, None, "kill", "kills", "killed", "killed"),
+            LeanToward => ("LeanToward", 2, None,
+                           "lean toward", "leans toward", "leaned toward", "leaned toward"),
+            Lock => ("Lock", 2, Some(Unlock), "lock", "locks", "locked", "locked"),
+            Move => ("Move", 3, None, "move", "moves", "moved", "moved"),
+            Open => ("Open", 2, Some(Close), "open", "opens", "opened", "opened"),
+            PickUp => ("PickUp", 2, Some(PutDown),
+                       "pick up", "picks up", "picked up", "picked up"),
+            Play => ("Play", 3, None, "play", "plays", "played", "played"),
+            PutDown => ("PutDown", 2, Some(PickUp),
+                        "put down", "puts down", "put down", "put down"),
+            SleepIn => ("SleepIn", 2, Some(WakeUpIn),
+                        "sleep in", "sleeps in", "slept in", "slept in"),
+            StandOn => ("StandOn", 2, None, "stand on", "stands on", "stood on", "stood on"),
+            Talk => ("Talk", 2, None, "talk to", "talks to", "talked to", "talked to"),
+            WakeUpIn => ("WakeUpIn", 2, Some(SleepIn),
+                         "wake up in", "wakes up in", "woke up in", "woken up in"),
+            WalkThrough => ("WalkThrough", 2, None,
+                            "walk through", "walks through", "walked through", "walked through"),
+            Unlock => ("Unlock", 2, Some(Lock), "unlock", "unlocks", "unlocked", "unlocked"),
+            Custom(ref info) => (&info.name, info.arity, info.inverse.clone(),
+                                 &info.base, &info.present, &info.past, &info.participle),
+        }
+    }
+
+    /// Returns metadata about the verb.
+    pub fn info(&self) -> VerbInfo {
+        if let Verb::Custom(ref info) = *self {return (**info).clone()};
+        let (name, arity, inverse, base, present, past, participle) = self.parts();
+        VerbInfo {
+            name: name.into(), arity, inverse,
+            base: base.into(), present: present.into(), past: past.into(), participle: participle.into(),
+        }
+    }
+
+    /// Returns the name, e.g. "PickUp".
+    pub fn name(&self) -> &str {self.parts().0}
+
+    /// Returns the number of participants, including the subject.
+    pub fn arity(&self) -> usize {self.parts().1}
+
+    /// Returns the verb that undoes this verb, as declared in its metadata.
+    pub fn inverse(&self) -> Option<Verb> {self.parts().2}
+
+    /// Returns the base form in English, e.g. "pick up".
+    pub fn base(&self) -> &str {self.parts().3}
+
+    /// Returns the third person present tense in English, e.g. "picks up".
+    pub fn present(&self) -> &str {self.parts().4}
+
+    /// Returns the past tense in English, e.g. "picked up".
+    pub fn past(&self) -> &str {self.parts().5}
+
+    /// Returns the past participle in English, e.g. "given".
+    pub fn participle(&self) -> &str {self.parts().6}
 }